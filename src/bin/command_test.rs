@@ -0,0 +1,28 @@
+//! Checks which commands the TCP client refuses to send to the robot.
+use phoenix_gui::gui::tab_types::tcp_client::connection::{command_error, send_command, MAX_COMMAND_LEN};
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+
+fn main() {
+    assert_eq!(command_error("KPDRIVE=1.5"), None);
+    assert_eq!(command_error(&"x".repeat(MAX_COMMAND_LEN)), None);
+    assert!(command_error(&"x".repeat(MAX_COMMAND_LEN + 1)).is_some());
+    // the robot ends the session when it reads "close" anywhere
+    for command in ["close", "close_gripper", "GRIPPER=close", "enclosed=1"] {
+        assert!(command_error(command).is_some(), "{}", command);
+    }
+    assert_eq!(command_error("CLOSE_GRIPPER"), None);
+
+    // refused commands aren't written, the others are sent on their own line
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    assert!(send_command(&mut client, "close_gripper").is_err());
+    send_command(&mut client, "KPDRIVE=1.5").unwrap();
+    drop(client);
+    let mut received = String::new();
+    server.read_to_string(&mut received).unwrap();
+    assert_eq!(received, "KPDRIVE=1.5\n");
+
+    println!("All command tests passed");
+}
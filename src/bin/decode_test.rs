@@ -0,0 +1,108 @@
+//! Feeds synthetic records of the robot to the decoder of the TCP client and checks the channels.
+use phoenix_gui::gui::tab_types::tcp_client::decode::{decode_value, Decoder, ValueDecoder};
+use phoenix_rec::data_types::DataType;
+use phoenix_rec::Data;
+
+fn channel<'a>(decoder: &'a Decoder, name: &str) -> &'a [(f32, f32)] {
+    &decoder
+        .channels
+        .iter()
+        .find(|c| c.name == name)
+        .unwrap_or_else(|| panic!("channel \"{}\" is missing", name))
+        .points
+}
+
+fn main() {
+    // every data type decodes to as many named values as it has channels
+    let all = [
+        DataType::None(2),
+        DataType::Color(1, 2),
+        DataType::Distance(3),
+        DataType::CalcSpeed(4, 5),
+        DataType::SyncSpeed(6, 7),
+        DataType::RealSpeeds(8, 9),
+        DataType::DrivenDistance(1.5, 2.5),
+        DataType::SyncError(0.25),
+        DataType::Correction(-1.0, 1.0),
+        DataType::AverageSpeed(10.5, 11.5),
+        DataType::RGB((1, 2, 3), (4, 5, 6)),
+        DataType::CurTarSpeeds(100, 120),
+    ];
+    for value in &all {
+        let decoded = decode_value(value).unwrap_or_else(|e| panic!("{:?}: {}", value, e));
+        let expected = match value {
+            DataType::None(_) => 0,
+            _ => value.none() as usize,
+        };
+        assert_eq!(decoded.len(), expected, "{:?}", value);
+    }
+    assert_eq!(
+        decode_value(&DataType::RGB((1, 2, 3), (4, 5, 6))).unwrap(),
+        vec![
+            ("right r".to_string(), 1.0),
+            ("right g".to_string(), 2.0),
+            ("right b".to_string(), 3.0),
+            ("left r".to_string(), 4.0),
+            ("left g".to_string(), 5.0),
+            ("left b".to_string(), 6.0),
+        ]
+    );
+
+    // a recording with all kinds of records, including ones with missing values
+    let data = vec![
+        Data::Command("DriveDist(100)".to_string()),
+        Data::RecordData(0, all.to_vec()),
+        Data::RecordData(10, vec![DataType::AverageSpeed(1.0, 2.0), DataType::CurTarSpeeds(5, 6)]),
+        Data::RecordDataOption(20, vec![None, Some(DataType::SyncError(0.5)), None]),
+        Data::Command("Turn(90)".to_string()),
+        Data::RecordData(30, vec![DataType::AverageSpeed(3.0, 4.0)]),
+    ];
+    let decoder = Decoder::decode(&data);
+    assert_eq!(decoder.warning_count(), 0, "{:?}", decoder.warnings);
+    assert_eq!(decoder.records, data.len());
+    assert_eq!(
+        decoder.comments,
+        vec![
+            (0, None, "DriveDist(100)".to_string()),
+            (4, Some(20.0), "Turn(90)".to_string())
+        ]
+    );
+    // the channels are in the order they first appear and keep their names
+    assert_eq!(decoder.channels[0].name, "right color");
+    assert_eq!(decoder.channels.len(), all.iter().map(|v| decode_value(v).unwrap().len()).sum::<usize>());
+    assert_eq!(
+        channel(&decoder, "right average speed"),
+        &[(0.0, 10.5), (10.0, 1.0), (30.0, 3.0)]
+    );
+    assert_eq!(channel(&decoder, "left average speed"), &[(0.0, 11.5), (10.0, 2.0), (30.0, 4.0)]);
+    assert_eq!(channel(&decoder, "target speed"), &[(0.0, 120.0), (10.0, 6.0)]);
+    assert_eq!(channel(&decoder, "sync error"), &[(0.0, 0.25), (20.0, 0.5)]);
+    assert_eq!(channel(&decoder, "left b"), &[(0.0, 6.0)]);
+
+    // pushing the records one by one gives the same result as decoding them at once
+    let mut incremental = Decoder::default();
+    data.iter().for_each(|d| incremental.push(d));
+    assert_eq!(incremental.channels, decoder.channels);
+
+    // values that can't be decoded are counted by reason and the rest of the recording is still decoded
+    let reject_distance: ValueDecoder = |value| match value {
+        DataType::Distance(_) => Err("Distance is not supported".to_string()),
+        _ => decode_value(value),
+    };
+    let mut partial = Decoder::with_value_decoder(reject_distance);
+    partial.push(&Data::RecordData(0, vec![DataType::Distance(3), DataType::SyncError(0.5)]));
+    assert_eq!(partial.warning_count(), 1);
+    partial.push(&Data::RecordData(10, vec![DataType::Distance(4)]));
+    partial.push(&Data::RecordData(20, vec![DataType::SyncError(1.5)]));
+    assert_eq!(partial.warning_count(), 2);
+    assert_eq!(partial.warnings.get("Distance is not supported"), Some(&2), "{:?}", partial.warnings);
+    assert_eq!(partial.records, 3);
+    assert_eq!(channel(&partial, "sync error"), &[(0.0, 0.5), (20.0, 1.5)]);
+    assert!(partial.channels.iter().all(|c| c.name != "dist"));
+
+    // an empty recording has no channels
+    let empty = Decoder::decode(&[]);
+    assert!(empty.channels.is_empty() && empty.comments.is_empty());
+
+    println!("All decoder tests passed");
+}
@@ -0,0 +1,46 @@
+//! Renders a csv file to an svg or png without opening a window, e.g. for reports.
+//!
+//! Usage: export_plot <data.csv> <output.svg|output.png> [width] [height] [scale] [--light]
+use phoenix_gui::export::{save, ExportSettings};
+use phoenix_gui::gui::tab_types::plot_file::PlotFile;
+use phoenix_gui::gui::tab_types::TabStruct;
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let light = args.iter().any(|a| a == "--light");
+    let args = args.into_iter().filter(|a| a != "--light").collect::<Vec<_>>();
+    if args.len() < 2 {
+        eprintln!("Usage: export_plot <data.csv> <output.svg|output.png> [width] [height] [scale] [--light]");
+        std::process::exit(1);
+    }
+    let mut settings = ExportSettings {
+        dark: !light,
+        ..Default::default()
+    };
+    let number = |i: usize, default: f32| match args.get(i) {
+        Some(value) => value.parse::<f32>().unwrap_or_else(|_| {
+            eprintln!("\"{}\" is not a number", value);
+            std::process::exit(1);
+        }),
+        None => default,
+    };
+    settings.width = number(2, settings.width);
+    settings.height = number(3, settings.height);
+    settings.scale = number(4, settings.scale);
+
+    let mut plot = PlotFile::default();
+    plot.load_file_name = args[0].clone();
+    plot.load_data();
+    if let Some(error) = &plot.loading_error {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+    let figure = plot.figure().expect("plot files always have a figure");
+    match save(&figure, &args[1], &settings) {
+        Ok(()) => println!("Saved the plot to \"{}\"", args[1]),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
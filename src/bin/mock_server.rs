@@ -0,0 +1,348 @@
+//! A server that speaks the protocol of phoenix_rec, so the TCP client can be developed without a robot.
+//! It replays a csv file written by phoenix_rec or generates signals for every data type,
+//! and can simulate disconnects and bad packets. Commands of the client are printed.
+//!
+//! Usage: mock_server [--replay data.csv] [--signal sine|steps|noise] [--rate 50] [--port 3333]
+//!                    [--disconnect-after 10] [--bad-every 100] [--loop]
+use lz4_compression::prelude::compress;
+use phoenix_gui::gui::tab_types::plot_file::table::parse_csv;
+use phoenix_rec::client::PORT;
+use phoenix_rec::data_types::DataType;
+use phoenix_rec::Data;
+use std::f32::consts::PI;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "Usage: mock_server [--replay data.csv] [--signal sine|steps|noise] [--rate 50] \
+[--port 3333] [--disconnect-after 10] [--bad-every 100] [--loop]";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Signal {
+    Sine,
+    Steps,
+    Noise,
+}
+
+struct Options {
+    replay: Option<String>,
+    signal: Signal,
+    /// records per second
+    rate: f64,
+    port: u16,
+    /// the connection is dropped without closing it after this many seconds
+    disconnect_after: Option<f64>,
+    /// every nth packet can't be decoded
+    bad_every: Option<usize>,
+    /// start the replay again at the end of the file
+    repeat: bool,
+}
+
+fn number<T: std::str::FromStr>(value: String) -> Result<T, String> {
+    value.parse().map_err(|_| format!("\"{}\" is not a valid number", value))
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        replay: None,
+        signal: Signal::Sine,
+        rate: 50.0,
+        port: PORT,
+        disconnect_after: None,
+        bad_every: None,
+        repeat: false,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--replay" => options.replay = Some(value()?),
+            "--signal" => {
+                options.signal = match value()?.as_str() {
+                    "sine" => Signal::Sine,
+                    "steps" => Signal::Steps,
+                    "noise" => Signal::Noise,
+                    other => return Err(format!("Unknown signal \"{}\"", other)),
+                }
+            }
+            "--rate" => options.rate = number(value()?)?,
+            "--port" => options.port = number(value()?)?,
+            "--disconnect-after" => options.disconnect_after = Some(number(value()?)?),
+            "--bad-every" => options.bad_every = Some(number(value()?)?),
+            "--loop" => options.repeat = true,
+            _ => return Err(format!("Unknown argument \"{}\"", arg)),
+        }
+    }
+    if options.rate <= 0.0 {
+        return Err("The rate has to be positive".to_string());
+    }
+    Ok(options)
+}
+
+/// One data type of every kind, except None
+fn data_types() -> Vec<DataType> {
+    (1..=u8::MAX).map_while(DataType::from_repr).collect()
+}
+
+/// Builds a data type of the same kind as `kind` from its values, in the order of its description
+fn with_values(kind: &DataType, v: &[f32]) -> DataType {
+    let i = |i: usize| v[i].round() as i16;
+    match kind {
+        DataType::None(n) => DataType::None(*n),
+        DataType::Color(_, _) => DataType::Color(i(0), i(1)),
+        DataType::Distance(_) => DataType::Distance(i(0)),
+        DataType::CalcSpeed(_, _) => DataType::CalcSpeed(i(0), i(1)),
+        DataType::SyncSpeed(_, _) => DataType::SyncSpeed(i(0), i(1)),
+        DataType::RealSpeeds(_, _) => DataType::RealSpeeds(i(0), i(1)),
+        DataType::DrivenDistance(_, _) => DataType::DrivenDistance(v[0], v[1]),
+        DataType::SyncError(_) => DataType::SyncError(v[0]),
+        DataType::Correction(_, _) => DataType::Correction(v[0], v[1]),
+        DataType::AverageSpeed(_, _) => DataType::AverageSpeed(v[0], v[1]),
+        DataType::RGB(_, _) => DataType::RGB((i(0), i(1), i(2)), (i(3), i(4), i(5))),
+        DataType::CurTarSpeeds(_, _) => DataType::CurTarSpeeds(i(0), i(1)),
+    }
+}
+
+/// Reads a csv file written by phoenix_rec, the data types are found by the names of their columns.
+/// Comments become commands.
+fn read_replay(path: &str) -> Result<Vec<Data>, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read \"{}\": {}", path, e))?;
+    let table = parse_csv(&contents).ok_or(format!("\"{}\" has no header", path))?;
+    let time = table.column("time").or_else(|| table.column("t"));
+    // the data types whose columns are all in the file, with the indices of the columns
+    let kinds = data_types()
+        .into_iter()
+        .filter_map(|kind| {
+            let columns = kind
+                .write_description()
+                .split(", ")
+                .map(|name| table.column(name))
+                .collect::<Option<Vec<_>>>()?;
+            Some((kind, columns))
+        })
+        .collect::<Vec<_>>();
+    if kinds.is_empty() {
+        return Err(format!(
+            "\"{}\" has none of the columns of phoenix_rec, like \"sync error\" or \"right color\"",
+            path
+        ));
+    }
+    let mut comments = table.comments.iter().peekable();
+    let mut data = vec![];
+    for (i, row) in table.rows.iter().enumerate() {
+        while let Some((_, _, text)) = comments.next_if(|(_, row, _)| *row <= i) {
+            data.push(Data::Command(text.clone()));
+        }
+        let t = match time.and_then(|c| row.get(c).copied().flatten()) {
+            Some(t) => t.max(0.0) as u128,
+            None => i as u128,
+        };
+        let values = kinds
+            .iter()
+            .filter_map(|(kind, columns)| {
+                let values = columns
+                    .iter()
+                    .map(|c| row.get(*c).copied().flatten())
+                    .collect::<Option<Vec<_>>>()?;
+                Some(with_values(kind, &values))
+            })
+            .collect();
+        data.push(Data::RecordData(t, values));
+    }
+    data.extend(comments.map(|(_, _, text)| Data::Command(text.clone())));
+    Ok(data)
+}
+
+/// Value of the signal between -1 and 1 at `t` seconds, channels are shifted by `phase`
+fn signal(signal: Signal, t: f32, phase: f32) -> f32 {
+    match signal {
+        Signal::Sine => (2.0 * PI * 0.5 * t + phase).sin(),
+        // a staircase with 4 levels and a step every 2 seconds
+        Signal::Steps => ((t / 2.0 + phase).floor().rem_euclid(4.0) - 1.5) / 1.5,
+        Signal::Noise => rand::random::<f32>() * 2.0 - 1.0,
+    }
+}
+
+/// A record with every data type at `t` ms
+fn synthetic_record(shape: Signal, t: u128) -> Data {
+    let seconds = t as f32 / 1000.0;
+    let values = data_types()
+        .into_iter()
+        .enumerate()
+        .map(|(k, kind)| {
+            let channels = kind.none() as usize;
+            let values = (0..channels)
+                .map(|c| {
+                    let s = signal(shape, seconds, (k * channels + c) as f32 * 0.7);
+                    match kind {
+                        DataType::Color(_, _) => 50.0 + 40.0 * s,
+                        DataType::Distance(_) => 100.0 + 80.0 * s,
+                        DataType::DrivenDistance(_, _) => 300.0 * seconds + 10.0 * s,
+                        DataType::SyncError(_) => 5.0 * s,
+                        DataType::Correction(_, _) => 2.0 * s,
+                        DataType::RGB(_, _) => 128.0 + 100.0 * s,
+                        _ => 300.0 * s,
+                    }
+                })
+                .collect::<Vec<_>>();
+            with_values(&kind, &values)
+        })
+        .collect();
+    Data::RecordData(t, values)
+}
+
+/// A packet like the server of phoenix_rec sends it: the length as u32 followed by the lz4 compressed records
+fn packet(data: &[Data]) -> Vec<u8> {
+    let data = compress(&bincode::serialize(data).expect("records can always be serialized"));
+    let mut packet = (data.len() as u32).to_le_bytes().to_vec();
+    packet.extend(data);
+    packet
+}
+
+/// A packet with a valid length but contents that can't be decoded
+fn bad_packet() -> Vec<u8> {
+    let data = (0..32).map(|_| rand::random::<u8>()).collect::<Vec<_>>();
+    let mut packet = (data.len() as u32).to_le_bytes().to_vec();
+    packet.extend(data);
+    packet
+}
+
+/// Prints the commands of the client, `closed` is set when it wants to close the connection.
+/// Like the phoenix_rec server it reads 50 bytes at a time into the same buffer and ends the session
+/// when the buffer contains "close" anywhere, also in the middle of a command.
+fn read_commands(mut stream: TcpStream, closed: Arc<AtomicBool>) {
+    let mut buffer = [0u8; 50];
+    let mut line = String::new();
+    loop {
+        let n = match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        if String::from_utf8_lossy(&buffer).contains("close") {
+            println!("Received \"close\", ending the session");
+            break;
+        }
+        for c in String::from_utf8_lossy(&buffer[..n]).chars() {
+            if c != '\n' {
+                line.push(c);
+            } else if !line.is_empty() {
+                println!("Received command: {}", line);
+                line.clear();
+            }
+        }
+    }
+    closed.store(true, Ordering::SeqCst);
+}
+
+/// What ended a connection
+enum End {
+    /// the client closed the connection or it broke
+    Closed,
+    /// all records of the file were sent
+    Finished,
+    Disconnect,
+}
+
+fn serve(mut stream: TcpStream, options: &Options, replay: &[Data]) -> Result<End, String> {
+    let mut hello = [0u8; 5];
+    stream
+        .read_exact(&mut hello)
+        .map_err(|e| format!("No handshake: {}", e))?;
+    if &hello != b"hello" {
+        return Err(format!("Unexpected handshake: {}", String::from_utf8_lossy(&hello)));
+    }
+    stream.write_all(b"hello").map_err(|e| e.to_string())?;
+    let closed = Arc::new(AtomicBool::new(false));
+    let reader = stream.try_clone().map_err(|e| e.to_string())?;
+    let reader_closed = closed.clone();
+    std::thread::spawn(move || read_commands(reader, reader_closed));
+
+    let start = Instant::now();
+    let period = Duration::from_secs_f64(1.0 / options.rate);
+    let mut next = start;
+    let mut replay_index = 0;
+    let mut packet_index = 0;
+    loop {
+        packet_index += 1;
+        if closed.load(Ordering::SeqCst) {
+            return Ok(End::Closed);
+        }
+        if let Some(after) = options.disconnect_after {
+            if start.elapsed().as_secs_f64() >= after {
+                // no packet of length 0, like a robot that lost its connection
+                let _ = stream.shutdown(Shutdown::Both);
+                return Ok(End::Disconnect);
+            }
+        }
+        let records = if options.replay.is_some() {
+            if replay_index >= replay.len() {
+                if !options.repeat {
+                    let _ = stream.write_all(&0u32.to_le_bytes());
+                    return Ok(End::Finished);
+                }
+                replay_index = 0;
+            }
+            // the commands are sent together with the record after them
+            let end = replay[replay_index..]
+                .iter()
+                .position(|d| !matches!(d, Data::Command(_)))
+                .map_or(replay.len(), |i| replay_index + i + 1);
+            let records = replay[replay_index..end].to_vec();
+            replay_index = end;
+            records
+        } else {
+            vec![synthetic_record(options.signal, start.elapsed().as_millis())]
+        };
+        let packet = match options.bad_every {
+            Some(n) if n > 0 && packet_index % n == 0 => bad_packet(),
+            _ => packet(&records),
+        };
+        if stream.write_all(&packet).is_err() {
+            return Ok(End::Closed);
+        }
+        next += period;
+        std::thread::sleep(next.saturating_duration_since(Instant::now()));
+    }
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(1);
+    });
+    let replay = match &options.replay {
+        Some(path) => read_replay(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => vec![],
+    };
+    let listener = TcpListener::bind(("0.0.0.0", options.port)).unwrap_or_else(|e| {
+        eprintln!("Could not listen on port {}: {}", options.port, e);
+        std::process::exit(1);
+    });
+    println!("Mock server listening on port {}", options.port);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Connection failed: {}", e);
+                continue;
+            }
+        };
+        let peer = stream
+            .peer_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        println!("Client connected: {}", peer);
+        match serve(stream, &options, &replay) {
+            Ok(End::Closed) => println!("Client {} disconnected", peer),
+            Ok(End::Finished) => println!("Sent the whole file to {}", peer),
+            Ok(End::Disconnect) => println!("Simulated a disconnect of {}", peer),
+            Err(e) => eprintln!("{}: {}", peer, e),
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Renders plots to SVG and PNG without a window or a GPU
+pub mod raster;
+pub mod svg;
+
+use crate::gui::plot_tools::layout::{format_value, AxisMap};
+use egui::{pos2, Align2, Color32, Pos2, Rect};
+use std::path::Path;
+
+const FONT_SIZE: f32 = 12.0;
+const TITLE_SIZE: f32 = 16.0;
+const MARGIN_LEFT: f32 = 70.0;
+const MARGIN_RIGHT: f32 = 20.0;
+/// extra space for the tick labels of a right axis
+const RIGHT_AXIS_WIDTH: f32 = 60.0;
+const MARGIN_TOP: f32 = 36.0;
+const MARGIN_BOTTOM: f32 = 46.0;
+const PANEL_GAP: f32 = 12.0;
+/// smallest difference in brightness between a series and a light background, out of 255
+const MIN_CONTRAST: f32 = 60.0;
+
+/// How the points of a series are drawn
+#[derive(Clone, Debug)]
+pub enum SeriesShape {
+    Line(Vec<[f64; 2]>),
+    /// points with the marker radius
+    Points(Vec<[f64; 2]>, f32),
+    /// bars from 0 to y, with the bar width in x units
+    Bars(Vec<[f64; 2]>, f64),
+    /// x and lower whisker, first quartile, median, third quartile and upper whisker, with the box width
+    Boxes(Vec<(f64, [f64; 5])>, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct Series {
+    pub name: String,
+    pub color: Color32,
+    pub shape: SeriesShape,
+}
+
+/// A vertical line with a label, e.g. a comment of the file
+#[derive(Clone, Debug)]
+pub struct Marker {
+    pub x: f64,
+    pub text: String,
+    pub color: Color32,
+}
+
+/// One plot of the figure, all panels share the x axis
+#[derive(Clone, Default)]
+pub struct Panel {
+    pub series: Vec<Series>,
+    pub markers: Vec<Marker>,
+    /// maps the values of the series on the right axis to the left one, the series are already mapped
+    pub right_axis: Option<AxisMap>,
+}
+
+/// Everything that is needed to draw the plots of a tab
+#[derive(Clone, Default)]
+pub struct Figure {
+    pub title: String,
+    pub x_label: String,
+    /// the visible x range, the range of the data if None
+    pub x_range: Option<(f64, f64)>,
+    pub panels: Vec<Panel>,
+}
+
+/// Size and look of an exported plot
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// size in points, the svg has this size
+    pub width: f32,
+    pub height: f32,
+    /// pixels per point of the png
+    pub scale: f32,
+    /// dark background like the app, otherwise white
+    pub dark: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            width: 1200.0,
+            height: 800.0,
+            scale: 2.0,
+            dark: true,
+        }
+    }
+}
+
+struct Theme {
+    background: Color32,
+    axes: Color32,
+    grid: Color32,
+    text: Color32,
+}
+
+impl Theme {
+    fn new(dark: bool) -> Theme {
+        if dark {
+            Theme {
+                background: Color32::from_gray(27),
+                axes: Color32::from_gray(160),
+                grid: Color32::from_gray(60),
+                text: Color32::from_gray(220),
+            }
+        } else {
+            Theme {
+                background: Color32::WHITE,
+                axes: Color32::from_gray(60),
+                grid: Color32::from_gray(220),
+                text: Color32::from_gray(20),
+            }
+        }
+    }
+
+    /// The color a series is drawn with. Colors that hardly differ from a light background,
+    /// like white or light yellow, are darkened so the series doesn't disappear.
+    fn series_color(&self, color: Color32) -> Color32 {
+        let background = brightness(self.background);
+        if background < 128.0 {
+            return color;
+        }
+        // the colors are premultiplied, the background shines through transparent ones
+        let see_through = background * (1.0 - color.a() as f32 / 255.0);
+        if background - (brightness(color) + see_through) >= MIN_CONTRAST {
+            return color;
+        }
+        let target = background - 2.0 * MIN_CONTRAST - see_through;
+        let factor = (target / brightness(color).max(1.0)).clamp(0.0, 1.0);
+        let [r, g, b, a] = color.to_array();
+        let scale = |c: u8| (c as f32 * factor).round() as u8;
+        Color32::from_rgba_premultiplied(scale(r), scale(g), scale(b), a)
+    }
+}
+
+/// Perceived brightness of the color channels, from 0 to 255
+fn brightness(color: Color32) -> f32 {
+    0.299 * color.r() as f32 + 0.587 * color.g() as f32 + 0.114 * color.b() as f32
+}
+
+/// The drawing operations the export backends have to support, coordinates are in points
+pub trait Canvas {
+    /// Restricts the following drawing to the rect, None removes the restriction
+    fn clip(&mut self, rect: Option<Rect>);
+    fn polyline(&mut self, points: &[Pos2], width: f32, color: Color32);
+    fn rect(&mut self, rect: Rect, fill: Color32, stroke: Option<(f32, Color32)>);
+    fn circle(&mut self, center: Pos2, radius: f32, color: Color32);
+    fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, size: f32, color: Color32);
+}
+
+/// Writes the figure to `path`, the format is chosen by the extension (svg or png)
+pub fn save(figure: &Figure, path: &str, settings: &ExportSettings) -> Result<(), String> {
+    let extension = Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "svg" => std::fs::write(path, svg::render(figure, settings))
+            .map_err(|e| format!("Could not write \"{}\": {}", path, e)),
+        "png" => raster::render(figure, settings)
+            .save(path)
+            .map_err(|e| format!("Could not write \"{}\": {}", path, e)),
+        _ => Err(format!("Unknown image format \"{}\", use svg or png", extension)),
+    }
+}
+
+/// Draws the title, the panels with their axes, series, markers and legends
+pub fn draw(figure: &Figure, canvas: &mut impl Canvas, settings: &ExportSettings) {
+    let theme = Theme::new(settings.dark);
+    let size = Rect::from_min_size(Pos2::ZERO, egui::vec2(settings.width, settings.height));
+    canvas.rect(size, theme.background, None);
+    canvas.text(
+        pos2(settings.width / 2.0, MARGIN_TOP / 2.0),
+        Align2::CENTER_CENTER,
+        &figure.title,
+        TITLE_SIZE,
+        theme.text,
+    );
+    let (x_min, x_max) = match figure.x_range.or_else(|| x_extent(figure)) {
+        Some((min, max)) if max > min => (min, max),
+        Some((min, _)) => (min - 0.5, min + 0.5),
+        None => (0.0, 1.0),
+    };
+    let right = if figure.panels.iter().any(|p| p.right_axis.is_some()) {
+        MARGIN_RIGHT + RIGHT_AXIS_WIDTH
+    } else {
+        MARGIN_RIGHT
+    };
+    let panels = figure.panels.len().max(1);
+    let panel_height = (settings.height
+        - MARGIN_TOP
+        - MARGIN_BOTTOM
+        - PANEL_GAP * (panels - 1) as f32)
+        / panels as f32;
+    for (i, panel) in figure.panels.iter().enumerate() {
+        let top = MARGIN_TOP + i as f32 * (panel_height + PANEL_GAP);
+        let frame = Rect::from_min_max(
+            pos2(MARGIN_LEFT, top),
+            pos2(settings.width - right, top + panel_height),
+        );
+        draw_panel(canvas, &theme, panel, frame, (x_min, x_max), i + 1 == panels);
+    }
+    canvas.text(
+        pos2(
+            MARGIN_LEFT + (settings.width - MARGIN_LEFT - right) / 2.0,
+            settings.height - 4.0,
+        ),
+        Align2::CENTER_BOTTOM,
+        &figure.x_label,
+        FONT_SIZE,
+        theme.text,
+    );
+}
+
+fn draw_panel(
+    canvas: &mut impl Canvas,
+    theme: &Theme,
+    panel: &Panel,
+    frame: Rect,
+    (x_min, x_max): (f64, f64),
+    x_labels: bool,
+) {
+    let (y_min, y_max) = match y_extent(panel, x_min, x_max) {
+        Some((min, max)) if max > min => {
+            let margin = (max - min) * 0.05;
+            (min - margin, max + margin)
+        }
+        Some((min, _)) => (min - 0.5, min + 0.5),
+        None => (0.0, 1.0),
+    };
+    let to_screen = |x: f64, y: f64| {
+        pos2(
+            frame.left() + ((x - x_min) / (x_max - x_min)) as f32 * frame.width(),
+            frame.bottom() - ((y - y_min) / (y_max - y_min)) as f32 * frame.height(),
+        )
+    };
+
+    // grid and tick labels
+    for x in nice_ticks(x_min, x_max) {
+        let screen_x = to_screen(x, y_min).x;
+        canvas.polyline(&[pos2(screen_x, frame.top()), pos2(screen_x, frame.bottom())], 1.0, theme.grid);
+        if x_labels {
+            canvas.text(
+                pos2(screen_x, frame.bottom() + 4.0),
+                Align2::CENTER_TOP,
+                &format_value(x),
+                FONT_SIZE,
+                theme.text,
+            );
+        }
+    }
+    for y in nice_ticks(y_min, y_max) {
+        let screen_y = to_screen(x_min, y).y;
+        canvas.polyline(&[pos2(frame.left(), screen_y), pos2(frame.right(), screen_y)], 1.0, theme.grid);
+        canvas.text(
+            pos2(frame.left() - 6.0, screen_y),
+            Align2::RIGHT_CENTER,
+            &format_value(y),
+            FONT_SIZE,
+            theme.text,
+        );
+    }
+    if let Some(map) = &panel.right_axis {
+        let (low, high) = (map.to_right(y_min), map.to_right(y_max));
+        for value in nice_ticks(low.min(high), low.max(high)) {
+            let screen_y = to_screen(x_min, map.to_left(value)).y;
+            canvas.polyline(&[pos2(frame.right(), screen_y), pos2(frame.right() + 4.0, screen_y)], 1.0, theme.axes);
+            canvas.text(
+                pos2(frame.right() + 6.0, screen_y),
+                Align2::LEFT_CENTER,
+                &format_value(value),
+                FONT_SIZE,
+                theme.text,
+            );
+        }
+    }
+
+    canvas.clip(Some(frame));
+    for series in &panel.series {
+        draw_series(canvas, series, theme.series_color(series.color), &to_screen);
+    }
+    for marker in &panel.markers {
+        let x = to_screen(marker.x, y_min).x;
+        canvas.polyline(&[pos2(x, frame.top()), pos2(x, frame.bottom())], 1.0, marker.color);
+        canvas.text(pos2(x + 3.0, frame.top() + 2.0), Align2::LEFT_TOP, &marker.text, FONT_SIZE, marker.color);
+    }
+    canvas.clip(None);
+    canvas.rect(frame, Color32::TRANSPARENT, Some((1.0, theme.axes)));
+    draw_legend(canvas, theme, panel, frame);
+}
+
+fn draw_series(
+    canvas: &mut impl Canvas,
+    series: &Series,
+    color: Color32,
+    to_screen: &impl Fn(f64, f64) -> Pos2,
+) {
+    match &series.shape {
+        SeriesShape::Line(points) => {
+            // gaps in the data split the line
+            for segment in points.split(|p| !p[0].is_finite() || !p[1].is_finite()) {
+                let segment = segment.iter().map(|p| to_screen(p[0], p[1])).collect::<Vec<_>>();
+                if segment.len() > 1 {
+                    canvas.polyline(&segment, 1.5, color);
+                }
+            }
+        }
+        SeriesShape::Points(points, radius) => {
+            for p in points.iter().filter(|p| p[0].is_finite() && p[1].is_finite()) {
+                canvas.circle(to_screen(p[0], p[1]), *radius, color);
+            }
+        }
+        SeriesShape::Bars(bars, width) => {
+            for bar in bars {
+                let rect = Rect::from_two_pos(
+                    to_screen(bar[0] - width / 2.0, 0.0),
+                    to_screen(bar[0] + width / 2.0, bar[1]),
+                );
+                canvas.rect(rect, color.gamma_multiply(0.5), Some((1.0, color)));
+            }
+        }
+        SeriesShape::Boxes(boxes, width) => {
+            for (x, [low, q1, median, q3, high]) in boxes {
+                let (left, right) = (x - width / 2.0, x + width / 2.0);
+                canvas.rect(
+                    Rect::from_two_pos(to_screen(left, *q1), to_screen(right, *q3)),
+                    color.gamma_multiply(0.3),
+                    Some((1.0, color)),
+                );
+                canvas.polyline(&[to_screen(left, *median), to_screen(right, *median)], 2.0, color);
+                canvas.polyline(&[to_screen(*x, *q3), to_screen(*x, *high)], 1.0, color);
+                canvas.polyline(&[to_screen(*x, *q1), to_screen(*x, *low)], 1.0, color);
+            }
+        }
+    }
+}
+
+fn draw_legend(canvas: &mut impl Canvas, theme: &Theme, panel: &Panel, frame: Rect) {
+    if panel.series.is_empty() {
+        return;
+    }
+    let row_height = FONT_SIZE + 4.0;
+    // the text isn't measured, this is about the width of the default font
+    let text_width = panel
+        .series
+        .iter()
+        .map(|s| s.name.chars().count())
+        .max()
+        .unwrap_or(0) as f32
+        * FONT_SIZE
+        * 0.55;
+    let legend = Rect::from_min_size(
+        pos2(frame.right() - text_width - 36.0, frame.top() + 6.0),
+        egui::vec2(text_width + 30.0, row_height * panel.series.len() as f32 + 6.0),
+    );
+    canvas.rect(legend, theme.background.gamma_multiply(0.85), Some((1.0, theme.grid)));
+    for (i, series) in panel.series.iter().enumerate() {
+        let y = legend.top() + 3.0 + row_height * (i as f32 + 0.5);
+        canvas.rect(
+            Rect::from_center_size(pos2(legend.left() + 11.0, y), egui::vec2(10.0, 10.0)),
+            theme.series_color(series.color),
+            None,
+        );
+        canvas.text(pos2(legend.left() + 22.0, y), Align2::LEFT_CENTER, &series.name, FONT_SIZE, theme.text);
+    }
+}
+
+fn shape_points(shape: &SeriesShape) -> Vec<[f64; 2]> {
+    match shape {
+        SeriesShape::Line(points) | SeriesShape::Points(points, _) => points.clone(),
+        SeriesShape::Bars(bars, width) => bars
+            .iter()
+            .flat_map(|b| [[b[0] - width / 2.0, 0.0], [b[0] + width / 2.0, b[1]]])
+            .collect(),
+        SeriesShape::Boxes(boxes, width) => boxes
+            .iter()
+            .flat_map(|(x, spread)| [[x - width / 2.0, spread[0]], [x + width / 2.0, spread[4]]])
+            .collect(),
+    }
+}
+
+fn x_extent(figure: &Figure) -> Option<(f64, f64)> {
+    let xs = figure
+        .panels
+        .iter()
+        .flat_map(|p| &p.series)
+        .flat_map(|s| shape_points(&s.shape))
+        .map(|p| p[0])
+        .filter(|x| x.is_finite());
+    xs.fold(None, |range, x| match range {
+        None => Some((x, x)),
+        Some((min, max)) => Some((f64::min(min, x), f64::max(max, x))),
+    })
+}
+
+/// y range of the points of the panel that are in the x range
+fn y_extent(panel: &Panel, x_min: f64, x_max: f64) -> Option<(f64, f64)> {
+    let ys = panel
+        .series
+        .iter()
+        .flat_map(|s| shape_points(&s.shape))
+        .filter(|p| p[0] >= x_min && p[0] <= x_max)
+        .map(|p| p[1])
+        .filter(|y| y.is_finite());
+    ys.fold(None, |range, y| match range {
+        None => Some((y, y)),
+        Some((min, max)) => Some((f64::min(min, y), f64::max(max, y))),
+    })
+}
+
+/// Round values between min and max, about 5 to 10 of them
+pub fn nice_ticks(min: f64, max: f64) -> Vec<f64> {
+    if max <= min || !min.is_finite() || !max.is_finite() {
+        return vec![];
+    }
+    let rough = (max - min) / 6.0;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|f| f * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    // multiply instead of adding up the steps, so there are no rounding errors like 0.30000000000000004
+    (first..=last).map(|i| i as f64 * step).collect()
+}
@@ -0,0 +1,218 @@
+use crate::export::{draw, Canvas, ExportSettings, Figure};
+use egui::epaint::text::{FontDefinitions, Fonts};
+use egui::epaint::{ClippedShape, Mesh, Primitive, Stroke, TessellationOptions, Tessellator};
+use egui::{Align2, Color32, FontId, Pos2, Rect, Rounding, Shape};
+use image::{Rgba, RgbaImage};
+
+/// largest side of the font atlas, there is no gpu that limits it
+const MAX_TEXTURE_SIDE: usize = 8192;
+
+/// Renders the figure into an image of `width * scale` by `height * scale` pixels.
+/// The shapes are tessellated like egui does it and the triangles are filled on the cpu.
+pub fn render(figure: &Figure, settings: &ExportSettings) -> RgbaImage {
+    let scale = settings.scale.max(0.1);
+    let fonts = Fonts::new(scale, MAX_TEXTURE_SIDE, FontDefinitions::default());
+    let mut canvas = RasterCanvas {
+        fonts: &fonts,
+        shapes: vec![],
+        clip_rect: Rect::EVERYTHING,
+    };
+    draw(figure, &mut canvas, settings);
+    let shapes = canvas.shapes;
+
+    // the glyphs are only added to the atlas when the text is laid out, so this has to come after drawing
+    let font_image = fonts.image();
+    let texture = Texture {
+        size: font_image.size,
+        coverage: font_image.srgba_pixels(None).map(|c| c.a() as f32 / 255.0).collect(),
+    };
+    let prepared_discs = fonts.texture_atlas().lock().prepared_discs();
+    let primitives = Tessellator::new(
+        scale,
+        TessellationOptions::default(),
+        fonts.font_image_size(),
+        prepared_discs,
+    )
+    .tessellate_shapes(shapes);
+
+    let width = (settings.width * scale).round().max(1.0) as u32;
+    let height = (settings.height * scale).round().max(1.0) as u32;
+    let mut target = Target {
+        width,
+        height,
+        pixels: vec![[0.0; 4]; (width * height) as usize],
+    };
+    for primitive in primitives {
+        if let Primitive::Mesh(mesh) = primitive.primitive {
+            let clip = Rect::from_min_max(
+                (primitive.clip_rect.min.to_vec2() * scale).to_pos2(),
+                (primitive.clip_rect.max.to_vec2() * scale).to_pos2(),
+            );
+            target.fill_mesh(&mesh, scale, clip, &texture);
+        }
+    }
+    target.into_image()
+}
+
+struct RasterCanvas<'a> {
+    fonts: &'a Fonts,
+    shapes: Vec<ClippedShape>,
+    clip_rect: Rect,
+}
+
+impl RasterCanvas<'_> {
+    fn add(&mut self, shape: Shape) {
+        self.shapes.push(ClippedShape {
+            clip_rect: self.clip_rect,
+            shape,
+        });
+    }
+}
+
+impl Canvas for RasterCanvas<'_> {
+    fn clip(&mut self, rect: Option<Rect>) {
+        self.clip_rect = rect.unwrap_or(Rect::EVERYTHING);
+    }
+
+    fn polyline(&mut self, points: &[Pos2], width: f32, color: Color32) {
+        self.add(Shape::line(points.to_vec(), Stroke::new(width, color)));
+    }
+
+    fn rect(&mut self, rect: Rect, fill: Color32, stroke: Option<(f32, Color32)>) {
+        self.add(Shape::rect_filled(rect, Rounding::ZERO, fill));
+        if let Some((width, color)) = stroke {
+            self.add(Shape::rect_stroke(rect, Rounding::ZERO, Stroke::new(width, color)));
+        }
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32, color: Color32) {
+        self.add(Shape::circle_filled(center, radius, color));
+    }
+
+    fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, size: f32, color: Color32) {
+        let shape = Shape::text(self.fonts, pos, anchor, text, FontId::proportional(size), color);
+        self.add(shape);
+    }
+}
+
+/// The coverage of the font atlas, the white pixel for untextured shapes is in there too
+struct Texture {
+    size: [usize; 2],
+    coverage: Vec<f32>,
+}
+
+impl Texture {
+    /// Bilinear sample at the normalized coordinates
+    fn sample(&self, uv: Pos2) -> f32 {
+        let [w, h] = self.size;
+        let x = (uv.x * w as f32 - 0.5).clamp(0.0, (w - 1) as f32);
+        let y = (uv.y * h as f32 - 0.5).clamp(0.0, (h - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+        let (fx, fy) = (x.fract(), y.fract());
+        let at = |x: usize, y: usize| self.coverage[y * w + x];
+        let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+        let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// Premultiplied srgba pixels, blended the same way as the egui renderers do it
+struct Target {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Target {
+    fn fill_mesh(&mut self, mesh: &Mesh, scale: f32, clip: Rect, texture: &Texture) {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertices = [
+                &mesh.vertices[triangle[0] as usize],
+                &mesh.vertices[triangle[1] as usize],
+                &mesh.vertices[triangle[2] as usize],
+            ];
+            self.fill_triangle(vertices, scale, clip, texture);
+        }
+    }
+
+    fn fill_triangle(
+        &mut self,
+        vertices: [&egui::epaint::Vertex; 3],
+        scale: f32,
+        clip: Rect,
+        texture: &Texture,
+    ) {
+        let mut p = vertices.map(|v| v.pos.to_vec2() * scale);
+        let mut v = vertices;
+        let edge = |a: egui::Vec2, b: egui::Vec2, c: egui::Vec2| {
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+        };
+        let mut area = edge(p[0], p[1], p[2]);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            p.swap(1, 2);
+            v.swap(1, 2);
+            area = -area;
+        }
+        // pixels exactly on an edge that two triangles share must only be filled once,
+        // so the edge belongs to the triangle that walks it in this direction
+        let owns = |a: egui::Vec2, b: egui::Vec2| {
+            let d = b - a;
+            d.y > 0.0 || (d.y == 0.0 && d.x < 0.0)
+        };
+        let owned = [owns(p[1], p[2]), owns(p[2], p[0]), owns(p[0], p[1])];
+
+        let min_x = p.iter().map(|p| p.x).fold(f32::INFINITY, f32::min).max(clip.left()).max(0.0);
+        let max_x = p.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).min(clip.right()).min(self.width as f32);
+        let min_y = p.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).max(clip.top()).max(0.0);
+        let max_y = p.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).min(clip.bottom()).min(self.height as f32);
+        if min_x >= max_x || min_y >= max_y {
+            return;
+        }
+        let colors = v.map(|v| v.color.to_array().map(|c| c as f32 / 255.0));
+        for y in (min_y.floor() as u32)..(max_y.ceil() as u32).min(self.height) {
+            for x in (min_x.floor() as u32)..(max_x.ceil() as u32).min(self.width) {
+                let center = egui::vec2(x as f32 + 0.5, y as f32 + 0.5);
+                if !clip.contains(center.to_pos2()) {
+                    continue;
+                }
+                let w = [
+                    edge(p[1], p[2], center),
+                    edge(p[2], p[0], center),
+                    edge(p[0], p[1], center),
+                ];
+                if (0..3).any(|i| w[i] < 0.0 || (w[i] == 0.0 && !owned[i])) {
+                    continue;
+                }
+                let b = w.map(|w| w / area);
+                let uv = (v[0].uv.to_vec2() * b[0] + v[1].uv.to_vec2() * b[1] + v[2].uv.to_vec2() * b[2]).to_pos2();
+                let coverage = texture.sample(uv);
+                let mut source = [0.0; 4];
+                for (c, value) in source.iter_mut().enumerate() {
+                    *value = (colors[0][c] * b[0] + colors[1][c] * b[1] + colors[2][c] * b[2]) * coverage;
+                }
+                let pixel = &mut self.pixels[(y * self.width + x) as usize];
+                for c in 0..4 {
+                    pixel[c] = source[c] + pixel[c] * (1.0 - source[3]);
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let [r, g, b, a] = self.pixels[(y * self.width + x) as usize];
+            let unmultiply = |c: f32| {
+                if a > 0.0 {
+                    (c / a * 255.0).round().clamp(0.0, 255.0) as u8
+                } else {
+                    0
+                }
+            };
+            Rgba([unmultiply(r), unmultiply(g), unmultiply(b), (a * 255.0).round().clamp(0.0, 255.0) as u8])
+        })
+    }
+}
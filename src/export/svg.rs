@@ -0,0 +1,152 @@
+use crate::export::{draw, Canvas, ExportSettings, Figure};
+use egui::{Align, Align2, Color32, Pos2, Rect};
+use std::fmt::Write;
+
+/// Renders the figure as an svg document
+pub fn render(figure: &Figure, settings: &ExportSettings) -> String {
+    let mut canvas = SvgCanvas::default();
+    draw(figure, &mut canvas, settings);
+    canvas.close_clip();
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n<defs>\n{defs}</defs>\n{body}</svg>\n",
+        w = settings.width,
+        h = settings.height,
+        defs = canvas.defs,
+        body = canvas.body
+    )
+}
+
+#[derive(Default)]
+struct SvgCanvas {
+    defs: String,
+    body: String,
+    clips: usize,
+    clipped: bool,
+}
+
+impl SvgCanvas {
+    fn close_clip(&mut self) {
+        if self.clipped {
+            self.body += "</g>\n";
+            self.clipped = false;
+        }
+    }
+}
+
+/// fill or stroke color with its opacity, e.g. `fill="rgb(1,2,3)" fill-opacity="0.5"`
+fn paint(attribute: &str, color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("{}=\"rgb({},{},{})\"", attribute, r, g, b)
+    } else {
+        format!(
+            "{a}=\"rgb({},{},{})\" {a}-opacity=\"{:.3}\"",
+            r,
+            g,
+            b,
+            a as f32 / 255.0,
+            a = attribute
+        )
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Canvas for SvgCanvas {
+    fn clip(&mut self, rect: Option<Rect>) {
+        self.close_clip();
+        if let Some(rect) = rect {
+            self.clips += 1;
+            let _ = writeln!(
+                self.defs,
+                "<clipPath id=\"clip{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+                self.clips,
+                rect.left(),
+                rect.top(),
+                rect.width(),
+                rect.height()
+            );
+            let _ = writeln!(self.body, "<g clip-path=\"url(#clip{})\">", self.clips);
+            self.clipped = true;
+        }
+    }
+
+    fn polyline(&mut self, points: &[Pos2], width: f32, color: Color32) {
+        let mut coordinates = String::with_capacity(points.len() * 16);
+        for p in points {
+            let _ = write!(coordinates, "{:.2},{:.2} ", p.x, p.y);
+        }
+        let _ = writeln!(
+            self.body,
+            "<polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+            coordinates.trim_end(),
+            paint("stroke", color),
+            width
+        );
+    }
+
+    fn rect(&mut self, rect: Rect, fill: Color32, stroke: Option<(f32, Color32)>) {
+        let fill = if fill == Color32::TRANSPARENT {
+            "fill=\"none\"".to_string()
+        } else {
+            paint("fill", fill)
+        };
+        let stroke = match stroke {
+            Some((width, color)) => format!(" {} stroke-width=\"{}\"", paint("stroke", color), width),
+            None => String::new(),
+        };
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {}{}/>",
+            rect.left(),
+            rect.top(),
+            rect.width(),
+            rect.height(),
+            fill,
+            stroke
+        );
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32, color: Color32) {
+        let _ = writeln!(
+            self.body,
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" {}/>",
+            center.x,
+            center.y,
+            radius,
+            paint("fill", color)
+        );
+    }
+
+    fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, size: f32, color: Color32) {
+        if text.is_empty() {
+            return;
+        }
+        let text_anchor = match anchor.x() {
+            Align::Min => "start",
+            Align::Center => "middle",
+            Align::Max => "end",
+        };
+        let baseline = match anchor.y() {
+            Align::Min => "hanging",
+            Align::Center => "central",
+            Align::Max => "text-after-edge",
+        };
+        let _ = writeln!(
+            self.body,
+            "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{}\" text-anchor=\"{}\" dominant-baseline=\"{}\" {}>{}</text>",
+            pos.x,
+            pos.y,
+            size,
+            text_anchor,
+            baseline,
+            paint("fill", color),
+            escape(text)
+        );
+    }
+}
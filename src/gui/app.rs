@@ -4,8 +4,11 @@ use egui_dock::{NodeIndex, SurfaceIndex, TabIndex};
 #[cfg(not(target_arch = "wasm32"))]
 use sysinfo::System;
 
+use crate::export::{save, ExportSettings};
 use crate::gui::file_viewer::FileViewer;
 use crate::gui::tab_types::image::ImageTab;
+use crate::gui::tab_types::plot_file::formats;
+use crate::gui::tab_types::plot_file::formats::DataFormat;
 use crate::gui::tab_types::plot_file::PlotFile;
 use crate::gui::tab_types::PlotType;
 use crate::gui::tab_types::PlotType::AllColors;
@@ -30,6 +33,10 @@ pub struct PhoenixGUI {
     screenshot_file: String,
     #[serde(skip)]
     do_screenshot: bool,
+    /// size and theme of exported plots
+    export: ExportSettings,
+    #[serde(skip)]
+    export_message: Option<Result<String, String>>,
 }
 
 impl Default for PhoenixGUI {
@@ -53,6 +60,8 @@ impl Default for PhoenixGUI {
             plot_rect: None,
             screenshot_file: "screenshot.png".to_string(),
             do_screenshot: false,
+            export: ExportSettings::default(),
+            export_message: None,
         }
     }
 }
@@ -66,7 +75,9 @@ impl PhoenixGUI {
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            let mut app: PhoenixGUI = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            app.tabs.upgrade();
+            return app;
         }
 
         Default::default()
@@ -156,7 +167,11 @@ impl eframe::App for PhoenixGUI {
                     // }
                     if ui.button("Open File").clicked() {
                         let mut plot = PlotFile::default();
-                        if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("data", &formats::extensions())
+                            .add_filter("all files", &["*"])
+                            .pick_file()
+                        {
                             plot.load_file_name = path.display().to_string();
                         }
                         plot.load_data();
@@ -183,6 +198,8 @@ impl eframe::App for PhoenixGUI {
                         ui.close_menu();
                         ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
                     }
+
+                    ui.menu_button("Export Plot", |ui| self.export_ui(ui));
                 });
             });
             // if ctrl+shift+s is pressed, save screenshot
@@ -249,7 +266,7 @@ impl eframe::App for PhoenixGUI {
                             return;
                         }
                         match path.extension().unwrap().to_str().unwrap() {
-                            "csv" => {
+                            _ if DataFormat::from_path(&path.display().to_string()).is_some() => {
                                 // first check if there's already a tab with this file
                                 let mut to_focus = None;
                                 for (surface_index, surface) in self.tabs.tree.iter_surfaces().enumerate() {
@@ -391,6 +408,65 @@ impl eframe::App for PhoenixGUI {
 }
 
 impl PhoenixGUI {
+    /// Exports the plot of the focused tab as svg or png, independent of the window size
+    fn export_ui(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.export;
+        ui.horizontal(|ui| {
+            ui.label("Size:");
+            ui.add(egui::DragValue::new(&mut settings.width).clamp_range(100.0..=10000.0));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut settings.height).clamp_range(100.0..=10000.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("PNG scale:");
+            ui.add(egui::DragValue::new(&mut settings.scale).speed(0.1).clamp_range(0.5..=8.0));
+            ui.label(format!(
+                "({} x {} px)",
+                (settings.width * settings.scale).round(),
+                (settings.height * settings.scale).round()
+            ));
+        });
+        ui.checkbox(&mut settings.dark, "Dark background");
+        let mut format = None;
+        ui.horizontal(|ui| {
+            if ui.button("SVG…").clicked() {
+                format = Some("svg");
+            }
+            if ui.button("PNG…").clicked() {
+                format = Some("png");
+            }
+        });
+        if let Some(format) = format {
+            self.export_message = self.export_focused_tab(format);
+        }
+        match &self.export_message {
+            Some(Ok(message)) => {
+                ui.colored_label(Color32::GREEN, message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(Color32::RED, error);
+            }
+            None => {}
+        }
+    }
+
+    /// Asks for a file name and writes the figure of the focused tab to it, None if the dialog was cancelled
+    fn export_focused_tab(&mut self, format: &str) -> Option<Result<String, String>> {
+        let Some((_, tab)) = self.tabs.tree.find_active_focused() else {
+            return Some(Err("No tab is focused".to_string()));
+        };
+        let Some(figure) = tab.plot.figure() else {
+            return Some(Err(format!("{} tabs can't be exported", tab.plot_type)));
+        };
+        let path = rfd::FileDialog::new()
+            .set_file_name(format!("plot.{}", format))
+            .add_filter(format, &[format])
+            .save_file()?
+            .display()
+            .to_string();
+        Some(save(&figure, &path, &self.export).map(|_| format!("Saved the plot to \"{}\"", path)))
+    }
+
     /// call this only if [self.file_viewer.error] is Some
     fn show_error_window(&mut self, ctx: &Context) {
         egui::Window::new("Error").show(ctx, |ui| {
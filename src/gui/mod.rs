@@ -1,4 +1,5 @@
 pub mod app;
 pub mod file_viewer;
+pub mod plot_tools;
 pub mod tab_types;
 pub mod tabs;
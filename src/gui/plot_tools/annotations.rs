@@ -0,0 +1,231 @@
+use crate::export::Marker;
+use egui::{Align2, Color32, DragValue, Ui};
+use egui_plot::{PlotPoint, PlotUi, Text, VLine};
+use std::fs;
+
+/// A label at an x value of the plot
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Annotation {
+    pub x: f64,
+    pub text: String,
+}
+
+/// What the user wants to do with the annotations, returned by [`Annotations::ui`]
+#[derive(PartialEq)]
+pub enum AnnotationAction {
+    None,
+    WriteToFile,
+    WriteToSidecar,
+}
+
+/// Markers for the comments of a file and annotations added by the user
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct Annotations {
+    pub show: bool,
+    /// annotations added by clicking on the plot, they are saved with the tab
+    pub user: Vec<Annotation>,
+    /// the next click on the plot adds an annotation
+    #[serde(skip)]
+    add_mode: bool,
+    #[serde(skip)]
+    selected: Option<usize>,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Annotations {
+            show: true,
+            user: vec![],
+            add_mode: false,
+            selected: None,
+        }
+    }
+}
+
+const FILE_COLOR: Color32 = Color32::from_rgb(150, 150, 150);
+const USER_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+/// how close to a marker a click has to be to select it, in pixels
+const CLICK_DISTANCE: f64 = 6.0;
+
+impl Annotations {
+    pub fn ui(&mut self, ui: &mut Ui) -> AnnotationAction {
+        let mut action = AnnotationAction::None;
+        ui.checkbox(&mut self.show, "Show annotations");
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.add_mode, "Add by clicking")
+                .on_hover_text("The next click on the plot adds an annotation at that x value");
+        });
+        let mut to_remove = None;
+        for (i, annotation) in self.user.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.add(DragValue::new(&mut annotation.x).speed(0.1).prefix("x: "));
+                let edit = ui.text_edit_singleline(&mut annotation.text);
+                if self.selected == Some(i) {
+                    edit.request_focus();
+                    self.selected = None;
+                }
+            });
+        }
+        if let Some(i) = to_remove {
+            self.user.remove(i);
+        }
+        if !self.user.is_empty() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Write to file")
+                    .on_hover_text("Insert the annotations as # comments into the csv file")
+                    .clicked()
+                {
+                    action = AnnotationAction::WriteToFile;
+                }
+                if ui
+                    .button("Write to sidecar")
+                    .on_hover_text("Save the annotations next to the file, they are loaded with it")
+                    .clicked()
+                {
+                    action = AnnotationAction::WriteToSidecar;
+                }
+            });
+        }
+        action
+    }
+
+    /// Draws the comments of the file and the user annotations, handles clicks on the plot.
+    /// Only draws the labels if `labels` is true, so stacked subplots don't repeat them.
+    pub fn draw(&mut self, plot_ui: &mut PlotUi, file: &[(f64, String)], labels: bool) {
+        if plot_ui.response().clicked() {
+            if let Some(pointer) = plot_ui.pointer_coordinate() {
+                self.clicked(plot_ui, pointer.x);
+            }
+        }
+        if !self.show {
+            return;
+        }
+        let top = plot_ui.plot_bounds().max()[1];
+        let user = self.user.iter().map(|a| (a.x, a.text.as_str(), USER_COLOR));
+        let file = file.iter().map(|(x, text)| (*x, text.as_str(), FILE_COLOR));
+        for (x, text, color) in file.chain(user) {
+            plot_ui.vline(VLine::new(x).color(color).width(1.0));
+            if labels {
+                plot_ui.text(
+                    Text::new(PlotPoint::new(x, top), format!(" {}", text))
+                        .color(color)
+                        .anchor(Align2::LEFT_TOP),
+                );
+            }
+        }
+    }
+
+    /// The comments of the file and the user annotations for exporting the plot
+    pub fn markers(&self, file: &[(f64, String)]) -> Vec<Marker> {
+        if !self.show {
+            return vec![];
+        }
+        let file = file.iter().map(|(x, text)| (*x, text, FILE_COLOR));
+        let user = self.user.iter().map(|a| (a.x, &a.text, USER_COLOR));
+        file.chain(user)
+            .map(|(x, text, color)| Marker {
+                x,
+                text: text.clone(),
+                color,
+            })
+            .collect()
+    }
+
+    fn clicked(&mut self, plot_ui: &PlotUi, x: f64) {
+        if self.add_mode {
+            self.user.push(Annotation {
+                x,
+                text: "annotation".to_string(),
+            });
+            self.selected = Some(self.user.len() - 1);
+            self.add_mode = false;
+            return;
+        }
+        // select the annotation next to the click, so it can be edited in the side panel
+        let bounds = plot_ui.plot_bounds();
+        let per_pixel = bounds.width() / plot_ui.response().rect.width() as f64;
+        self.selected = self
+            .user
+            .iter()
+            .position(|a| (a.x - x).abs() < CLICK_DISTANCE * per_pixel);
+    }
+}
+
+/// Path of the file the annotations of `file` are saved in
+pub fn sidecar_path(file: &str) -> String {
+    format!("{}.annotations.csv", file)
+}
+
+/// Reads annotations saved with [`write_sidecar`], lines have the format `x,text`
+pub fn read_sidecar(path: &str) -> Result<Vec<Annotation>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Could not read \"{}\": {}", path, e))?;
+    let mut annotations = vec![];
+    // the first line is the header
+    for (i, line) in contents.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (x, text) = line
+            .split_once(',')
+            .ok_or(format!("Missing text in \"{}\" (line {})", path, i + 1))?;
+        let x = x
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Could not parse x value \"{}\" (line {})", x, i + 1))?;
+        annotations.push(Annotation {
+            x,
+            text: text.trim().to_string(),
+        });
+    }
+    Ok(annotations)
+}
+
+pub fn write_sidecar(path: &str, annotations: &[Annotation]) -> Result<(), String> {
+    let mut contents = "x,text\n".to_string();
+    for annotation in annotations {
+        contents += &format!("{},{}\n", annotation.x, single_line(&annotation.text));
+    }
+    fs::write(path, contents).map_err(|e| format!("Could not write \"{}\": {}", path, e))
+}
+
+/// Inserts the annotations as `#` comments into a csv file, in front of the first data row
+/// with an x value that isn't smaller than the x value of the annotation.
+/// `row_x` are the x values of the data rows in the order they appear in the file.
+pub fn insert_comments(contents: &str, row_x: &[Option<f64>], annotations: &[Annotation]) -> String {
+    let mut annotations = annotations.to_vec();
+    annotations.sort_by(|a, b| a.x.total_cmp(&b.x));
+    let mut annotations = annotations.into_iter().peekable();
+    let mut result = String::new();
+    let mut row = 0;
+    let mut has_header = false;
+    for line in contents.lines() {
+        // the first line that isn't a comment is the header, empty lines and comments aren't data rows
+        let is_data = !line.is_empty() && !line.starts_with('#');
+        if is_data && has_header {
+            if let Some(Some(x)) = row_x.get(row) {
+                while let Some(annotation) = annotations.next_if(|a| a.x <= *x) {
+                    result += &format!("# {}\n", single_line(&annotation.text));
+                }
+            }
+            row += 1;
+        }
+        has_header |= is_data;
+        result += line;
+        result.push('\n');
+    }
+    for annotation in annotations {
+        result += &format!("# {}\n", single_line(&annotation.text));
+    }
+    result
+}
+
+fn single_line(text: &str) -> String {
+    text.replace(['\n', '\r'], " ")
+}
@@ -0,0 +1,247 @@
+use crate::gui::plot_tools::layout::format_value;
+use crate::signal::stats::{interpolate, region_stats, RegionStats};
+use egui::{Color32, Ui};
+use egui_plot::{PlotPoint, PlotUi, VLine};
+
+/// how close to a cursor the pointer has to be to drag it, in pixels
+const GRAB_DISTANCE: f32 = 6.0;
+const COLORS: [Color32; 2] = [
+    Color32::from_rgb(0, 200, 255),
+    Color32::from_rgb(255, 80, 200),
+];
+
+/// The values of one series at the cursors and between them
+#[derive(Clone)]
+pub struct Readout {
+    pub name: String,
+    /// unit of the y values, empty if unknown
+    pub unit: String,
+    pub y: [Option<f64>; 2],
+    pub stats: Option<RegionStats>,
+}
+
+/// Two vertical cursors that can be dragged over a plot to measure the series
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct Cursors {
+    pub enabled: bool,
+    /// x values of the cursors, placed in the visible range the first time they are shown
+    pub positions: Option<[f64; 2]>,
+    #[serde(skip)]
+    hovered: Option<usize>,
+    #[serde(skip)]
+    dragging: Option<usize>,
+    #[serde(skip)]
+    readout: Vec<Readout>,
+    /// unit of the x values, empty if unknown
+    #[serde(skip)]
+    x_unit: String,
+}
+
+impl Cursors {
+    /// Has to be called before the plots are drawn, so the readout only contains the series of this frame
+    pub fn clear(&mut self) {
+        self.readout.clear();
+    }
+
+    /// x of the first cursor, None if the cursors aren't shown
+    pub fn first(&self) -> Option<f64> {
+        self.positions.filter(|_| self.enabled).map(|p| p[0])
+    }
+
+    /// x range between the cursors, None if they aren't shown
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.positions
+            .filter(|_| self.enabled)
+            .map(|[a, b]| (a.min(b), a.max(b)))
+    }
+
+    /// The plot must not be dragged while a cursor is dragged
+    pub fn allow_plot_drag(&self) -> bool {
+        !self.enabled || (self.hovered.is_none() && self.dragging.is_none())
+    }
+
+    /// Draws the cursors and moves them when they are dragged
+    pub fn draw(&mut self, plot_ui: &mut PlotUi) {
+        if !self.enabled {
+            return;
+        }
+        let bounds = plot_ui.plot_bounds();
+        let positions = self.positions.get_or_insert_with(|| {
+            let (min, width) = (bounds.min()[0], bounds.width());
+            [min + width / 3.0, min + width * 2.0 / 3.0]
+        });
+        let response = plot_ui.response().clone();
+        if let Some(pointer) = plot_ui.pointer_coordinate() {
+            let pointer_x = plot_ui.screen_from_plot(pointer).x;
+            if !response.dragged() {
+                self.hovered = (0..2).find(|i| {
+                    let x = plot_ui.screen_from_plot(PlotPoint::new(positions[*i], 0.0)).x;
+                    (x - pointer_x).abs() < GRAB_DISTANCE
+                });
+            }
+            if response.drag_started() {
+                self.dragging = self.hovered;
+            }
+            if let Some(i) = self.dragging {
+                positions[i] = pointer.x;
+            }
+        }
+        if response.drag_stopped() {
+            self.dragging = None;
+        }
+        for (i, x) in positions.iter().enumerate() {
+            let width = if self.hovered == Some(i) || self.dragging == Some(i) {
+                2.5
+            } else {
+                1.5
+            };
+            plot_ui.vline(VLine::new(*x).color(COLORS[i]).width(width));
+        }
+    }
+
+    pub fn set_x_unit(&mut self, unit: &str) {
+        self.x_unit = unit.to_string();
+    }
+
+    /// Measures a series that is drawn on the plot
+    pub fn measure(&mut self, name: &str, points: &[[f64; 2]]) {
+        self.measure_with_unit(name, "", points);
+    }
+
+    /// Measures a series whose values are in `unit`, the unit is shown next to the values
+    pub fn measure_with_unit(&mut self, name: &str, unit: &str, points: &[[f64; 2]]) {
+        let Some([a, b]) = self.positions.filter(|_| self.enabled) else {
+            return;
+        };
+        self.readout.push(Readout {
+            name: name.to_string(),
+            unit: unit.to_string(),
+            y: [interpolate(points, a), interpolate(points, b)],
+            stats: region_stats(points, a, b),
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, "Show cursors");
+        if !self.enabled {
+            return;
+        }
+        let Some([a, b]) = self.positions else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            if let Some(positions) = &mut self.positions {
+                ui.colored_label(COLORS[0], "x1:");
+                ui.add(egui::DragValue::new(&mut positions[0]).speed(0.1));
+                ui.colored_label(COLORS[1], "x2:");
+                ui.add(egui::DragValue::new(&mut positions[1]).speed(0.1));
+            }
+            ui.label(format!("Δx: {}", with_unit(format_value(b - a), &self.x_unit)));
+        });
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            egui::Grid::new(ui.next_auto_id()).striped(true).show(ui, |ui| {
+                for header in ["series", "y1", "y2", "Δy", "slope", "mean", "min", "max", "rms"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for row in self.rows(format_value, true) {
+                    for value in row {
+                        ui.label(value);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Copy as text").clicked() {
+                ui.ctx().copy_text(self.to_text());
+            }
+            if ui.button("Copy as csv").clicked() {
+                ui.ctx().copy_text(self.to_csv());
+            }
+        });
+    }
+
+    /// The readout as a table of strings, one row per series.
+    /// With `units` the values are followed by their unit.
+    fn rows(&self, format: fn(f64) -> String, units: bool) -> Vec<Vec<String>> {
+        let Some([a, b]) = self.positions else {
+            return vec![];
+        };
+        self.readout
+            .iter()
+            .map(|r| {
+                let slope_unit = match (r.unit.is_empty(), self.x_unit.is_empty()) {
+                    (true, true) => String::new(),
+                    (false, true) => r.unit.clone(),
+                    (true, false) => format!("1/{}", self.x_unit),
+                    (false, false) => format!("{}/{}", r.unit, self.x_unit),
+                };
+                let value = |v: Option<f64>, unit: &str| match v {
+                    Some(v) if units => with_unit(format(v), unit),
+                    Some(v) => format(v),
+                    None => String::new(),
+                };
+                let dy = r.y[0].zip(r.y[1]).map(|(y1, y2)| y2 - y1);
+                let slope = dy.filter(|_| b != a).map(|dy| dy / (b - a));
+                vec![
+                    r.name.clone(),
+                    value(r.y[0], &r.unit),
+                    value(r.y[1], &r.unit),
+                    value(dy, &r.unit),
+                    value(slope, &slope_unit),
+                    value(r.stats.map(|s| s.mean), &r.unit),
+                    value(r.stats.map(|s| s.min), &r.unit),
+                    value(r.stats.map(|s| s.max), &r.unit),
+                    value(r.stats.map(|s| s.rms), &r.unit),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        let Some([a, b]) = self.positions else {
+            return String::new();
+        };
+        let mut text = format!(
+            "x1: {}, x2: {}, Δx: {}\n",
+            with_unit(format_value(a), &self.x_unit),
+            with_unit(format_value(b), &self.x_unit),
+            with_unit(format_value(b - a), &self.x_unit)
+        );
+        for row in self.rows(format_value, true) {
+            text += &format!(
+                "{}: y1 = {}, y2 = {}, Δy = {}, slope = {}, mean = {}, min = {}, max = {}, rms = {}\n",
+                row[0], row[1], row[2], row[3], row[4], row[5], row[6], row[7], row[8]
+            );
+        }
+        text
+    }
+
+    pub fn to_csv(&self) -> String {
+        let Some([a, b]) = self.positions else {
+            return String::new();
+        };
+        let mut csv = "series,unit,x1,x2,y1,y2,dy,slope,mean,min,max,rms\n".to_string();
+        for (row, readout) in self.rows(|v| v.to_string(), false).iter().zip(&self.readout) {
+            csv += &format!(
+                "{},{},{},{},{}\n",
+                row[0].replace(',', " "),
+                readout.unit.replace(',', " "),
+                a,
+                b,
+                row[1..].join(",")
+            );
+        }
+        csv
+    }
+}
+
+fn with_unit(value: String, unit: &str) -> String {
+    if unit.is_empty() {
+        value
+    } else {
+        format!("{} {}", value, unit)
+    }
+}
@@ -0,0 +1,312 @@
+use crate::signal::decimate::Overview;
+use crate::signal::filter::{apply_all, Filter, Pass};
+use egui::{DragValue, Ui};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// The filter pipeline of a single series
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct SeriesFilters {
+    pub filters: Vec<Filter>,
+    /// draw only the filtered series instead of drawing it next to the raw data
+    pub replace: bool,
+    #[serde(skip)]
+    cache: Option<FilterCache>,
+    #[serde(skip)]
+    growing: Option<GrowingCache>,
+}
+
+/// The last result, so we don't have to filter the whole series every frame
+#[derive(Clone)]
+struct FilterCache {
+    filters: Vec<Filter>,
+    /// hash of the points that were filtered, see [`hash_points`]
+    input: u64,
+    output: Vec<[f64; 2]>,
+}
+
+/// The filtered points of a series that only grows, see [`SeriesFilters::apply_growing`]
+#[derive(Clone)]
+struct GrowingCache {
+    filters: Vec<Filter>,
+    /// number of points that were filtered and the last of them, to notice when the series starts again
+    input: usize,
+    last_input: Option<(f32, f32)>,
+    output: Vec<[f64; 2]>,
+    /// the filtered points before `settled`, which don't change anymore when points are added
+    overview: Overview,
+    settled: usize,
+}
+
+impl GrowingCache {
+    fn new(filters: &[Filter]) -> Self {
+        GrowingCache {
+            filters: filters.to_vec(),
+            input: 0,
+            last_input: None,
+            output: vec![],
+            overview: Overview::default(),
+            settled: 0,
+        }
+    }
+}
+
+impl SeriesFilters {
+    pub fn is_active(&self) -> bool {
+        !self.filters.is_empty()
+    }
+
+    /// Runs the pipeline over the points, the result is cached until the points or the filters change
+    pub fn apply(&mut self, points: &[[f64; 2]]) -> &[[f64; 2]] {
+        let input = hash_points(points);
+        let up_to_date = self
+            .cache
+            .as_ref()
+            .is_some_and(|c| c.input == input && c.filters == self.filters);
+        if !up_to_date {
+            let x = points.iter().map(|p| p[0]).collect::<Vec<_>>();
+            let y = points.iter().map(|p| p[1]).collect::<Vec<_>>();
+            let y = apply_all(&self.filters, &x, &y);
+            self.cache = Some(FilterCache {
+                filters: self.filters.clone(),
+                input,
+                output: x.into_iter().zip(y).map(|(x, y)| [x, y]).collect(),
+            });
+        }
+        &self.cache.as_ref().unwrap().output
+    }
+
+    /// Forgets the filtered points, e.g. when the series started again
+    pub fn clear_cache(&mut self) {
+        self.cache = None;
+        self.growing = None;
+    }
+
+    /// Runs the pipeline over a series that only grows, like a live channel, and returns the filtered points.
+    /// When points were added only the end of the series is filtered again, as far as the new points
+    /// change the filtered values. Pipelines with a filter that depends on all samples before,
+    /// like a butterworth filter, are run over the whole series again.
+    pub fn apply_growing(&mut self, points: &[(f32, f32)]) -> &[[f64; 2]] {
+        let cache = self.growing.get_or_insert_with(|| GrowingCache::new(&self.filters));
+        let continues = cache.filters == self.filters
+            && cache.input <= points.len()
+            && cache.input.checked_sub(1).map(|i| points[i]) == cache.last_input;
+        if !continues {
+            *cache = GrowingCache::new(&self.filters);
+        }
+        if cache.input == points.len() {
+            return &cache.output;
+        }
+        let context = self.filters.iter().map(Filter::context).sum::<Option<usize>>();
+        // the filtered values that the new points change, and the points before them that they depend on
+        let (start, keep) = match context {
+            Some(context) => (
+                cache.input.saturating_sub(2 * context),
+                cache.input.saturating_sub(context),
+            ),
+            None => (0, 0),
+        };
+        let x = points[start..].iter().map(|p| p.0 as f64).collect::<Vec<_>>();
+        let y = points[start..].iter().map(|p| p.1 as f64).collect::<Vec<_>>();
+        let y = apply_all(&self.filters, &x, &y);
+        cache.output.truncate(keep);
+        cache.output.extend(
+            x.into_iter()
+                .zip(y)
+                .skip(keep - start)
+                .map(|(x, y)| [x, y]),
+        );
+        cache.input = points.len();
+        cache.last_input = points.last().copied();
+        if let Some(context) = context {
+            let settled = points.len().saturating_sub(context);
+            for point in &cache.output[cache.settled.min(settled)..settled] {
+                cache.overview.push(*point);
+            }
+            cache.settled = cache.settled.max(settled);
+        }
+        &cache.output
+    }
+
+    /// The points of [`SeriesFilters::apply_growing`] decimated like [`Overview::points`],
+    /// only the points at the end that can still change are returned as they are
+    pub fn growing_overview(&self) -> Vec<[f64; 2]> {
+        let Some(cache) = &self.growing else {
+            return vec![];
+        };
+        let mut points = cache.overview.points();
+        points.extend_from_slice(&cache.output[cache.settled.min(cache.output.len())..]);
+        points
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        let mut to_remove = None;
+        for (i, filter) in self.filters.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    to_remove = Some(i);
+                }
+                filter_ui(ui, filter);
+            });
+        }
+        if let Some(i) = to_remove {
+            self.filters.remove(i);
+        }
+        egui::ComboBox::from_id_source(ui.next_auto_id())
+            .selected_text("Add filter")
+            .show_ui(ui, |ui| {
+                for filter in Filter::all() {
+                    if ui.selectable_label(false, filter.to_string()).clicked() {
+                        self.filters.push(filter);
+                    }
+                }
+            });
+        ui.checkbox(&mut self.replace, "Hide raw data");
+    }
+}
+
+/// A hash of the values of the points, so a cached result is only used for exactly the same points
+pub fn hash_points(points: &[[f64; 2]]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for [x, y] in points {
+        x.to_bits().hash(&mut hasher);
+        y.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn filter_ui(ui: &mut Ui, filter: &mut Filter) {
+    match filter {
+        Filter::MovingAverage { window } => {
+            ui.label("Moving average");
+            ui.add(DragValue::new(window).clamp_range(1..=10000).prefix("window: "));
+        }
+        Filter::Median { window } => {
+            ui.label("Median");
+            ui.add(DragValue::new(window).clamp_range(1..=10000).prefix("window: "));
+        }
+        Filter::Exponential { alpha } => {
+            ui.label("Exponential");
+            ui.add(
+                DragValue::new(alpha)
+                    .speed(0.01)
+                    .clamp_range(0.0..=1.0)
+                    .prefix("alpha: "),
+            );
+        }
+        Filter::SavitzkyGolay { window, order } => {
+            ui.label("Savitzky-Golay");
+            ui.add(DragValue::new(window).clamp_range(3..=10001).prefix("window: "));
+            ui.add(DragValue::new(order).clamp_range(0..=10).prefix("order: "));
+        }
+        Filter::Butterworth {
+            pass,
+            order,
+            low,
+            high,
+            zero_phase,
+        } => {
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source(ui.next_auto_id())
+                        .selected_text(format!("Butterworth {}", pass))
+                        .show_ui(ui, |ui| {
+                            for option in [Pass::Low, Pass::High, Pass::Band] {
+                                ui.selectable_value(pass, option, option.to_string());
+                            }
+                        });
+                    ui.add(DragValue::new(order).clamp_range(1..=8).prefix("order: "));
+                });
+                ui.horizontal(|ui| {
+                    // cutoff frequencies are in cycles per unit of the x axis
+                    if *pass != Pass::Low {
+                        ui.add(DragValue::new(low).speed(0.0001).prefix("low: "));
+                    }
+                    if *pass != Pass::High {
+                        ui.add(DragValue::new(high).speed(0.0001).prefix("high: "));
+                    }
+                    ui.checkbox(zero_phase, "Zero phase");
+                });
+            });
+        }
+        Filter::Derivative | Filter::Integral => {
+            ui.label(filter.to_string());
+        }
+    }
+}
+
+/// The filter pipelines of all series of a plot, keyed by the name of the series
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct FilterPipelines {
+    pub series: HashMap<String, SeriesFilters>,
+    selected: String,
+}
+
+impl FilterPipelines {
+    pub fn ui(&mut self, ui: &mut Ui, names: &[String]) {
+        ui.horizontal(|ui| {
+            ui.label("Series: ");
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(self.selected.clone())
+                .show_ui(ui, |ui| {
+                    for name in names {
+                        let active = self.series.get(name).is_some_and(|s| s.is_active());
+                        let label = if active {
+                            format!("{} (filtered)", name)
+                        } else {
+                            name.clone()
+                        };
+                        ui.selectable_value(&mut self.selected, name.clone(), label);
+                    }
+                });
+        });
+        if !self.selected.is_empty() {
+            self.series
+                .entry(self.selected.clone())
+                .or_default()
+                .ui(ui);
+        }
+    }
+
+    /// Forgets the filtered points of all series
+    pub fn clear_caches(&mut self) {
+        self.series.values_mut().for_each(SeriesFilters::clear_cache);
+    }
+
+    /// Returns the lines that should be drawn for the series `name`: the raw data and/or the filtered data.
+    /// The bool is true for the line that should be highlighted, raw data drawn next to a filtered series isn't.
+    pub fn lines(
+        &mut self,
+        name: &str,
+        points: Vec<[f64; 2]>,
+    ) -> Vec<(String, Vec<[f64; 2]>, bool)> {
+        self.lines_with(name, points, |filters, points| filters.apply(points).to_vec())
+    }
+
+    /// Like [`FilterPipelines::lines`] for lines that aren't only a list of points,
+    /// `filter` makes the filtered line with the pipeline of the series
+    pub fn lines_with<T>(
+        &mut self,
+        name: &str,
+        line: T,
+        filter: impl FnOnce(&mut SeriesFilters, &T) -> T,
+    ) -> Vec<(String, T, bool)> {
+        match self.series.get_mut(name) {
+            Some(filters) if filters.is_active() => {
+                let filtered = filter(filters, &line);
+                if filters.replace {
+                    vec![(format!("{} (filtered)", name), filtered, true)]
+                } else {
+                    vec![
+                        (name.to_string(), line, false),
+                        (format!("{} (filtered)", name), filtered, true),
+                    ]
+                }
+            }
+            _ => vec![(name.to_string(), line, true)],
+        }
+    }
+}
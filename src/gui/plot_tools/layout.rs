@@ -0,0 +1,150 @@
+use egui::{DragValue, Ui};
+use egui_plot::{AxisHints, HPlacement};
+use std::collections::HashMap;
+
+/// Where a series is drawn
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct SeriesPlacement {
+    /// index of the stacked subplot, 0 is the top one
+    pub subplot: usize,
+    /// use the y axis on the right side of the subplot
+    pub right_axis: bool,
+}
+
+/// Stacked subplots with a shared x axis, the series can be moved between them
+/// and each subplot can have a second y axis
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct PlotLayout {
+    pub subplots: usize,
+    /// placement of the series, keyed by the series name
+    pub series: HashMap<String, SeriesPlacement>,
+}
+
+impl Default for PlotLayout {
+    fn default() -> Self {
+        PlotLayout {
+            subplots: 1,
+            series: HashMap::new(),
+        }
+    }
+}
+
+impl PlotLayout {
+    pub fn subplots(&self) -> usize {
+        self.subplots.max(1)
+    }
+
+    pub fn placement(&self, name: &str) -> SeriesPlacement {
+        let mut placement = self.series.get(name).copied().unwrap_or_default();
+        placement.subplot = placement.subplot.min(self.subplots() - 1);
+        placement
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, names: &[String]) {
+        ui.horizontal(|ui| {
+            ui.label("Subplots: ");
+            ui.add(DragValue::new(&mut self.subplots).clamp_range(1..=8));
+        });
+        egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            for name in names {
+                let mut placement = self.placement(name);
+                ui.label(name);
+                if self.subplots() > 1 {
+                    ui.add(
+                        DragValue::new(&mut placement.subplot)
+                            .clamp_range(0..=self.subplots() - 1)
+                            .prefix("subplot: "),
+                    );
+                }
+                ui.checkbox(&mut placement.right_axis, "Right axis");
+                ui.end_row();
+                if placement != SeriesPlacement::default() {
+                    self.series.insert(name.clone(), placement);
+                } else {
+                    self.series.remove(name);
+                }
+            }
+        });
+    }
+}
+
+/// egui_plot only has one coordinate system per plot, so the series of the right axis
+/// are mapped linearly onto the range of the left axis and the right axis shows the original values
+#[derive(Clone, Copy, Debug)]
+pub struct AxisMap {
+    scale: f64,
+    offset: f64,
+}
+
+impl AxisMap {
+    /// Maps the range `right` onto the range `left`, if there is nothing on the left axis the values aren't changed
+    pub fn new(left: Option<(f64, f64)>, right: Option<(f64, f64)>) -> Self {
+        match (left, right) {
+            (Some((left_min, left_max)), Some((right_min, right_max))) => {
+                let right_span = right_max - right_min;
+                let left_span = left_max - left_min;
+                let scale = if right_span > 0.0 && left_span > 0.0 {
+                    left_span / right_span
+                } else {
+                    1.0
+                };
+                AxisMap {
+                    scale,
+                    offset: left_min - right_min * scale,
+                }
+            }
+            _ => AxisMap {
+                scale: 1.0,
+                offset: 0.0,
+            },
+        }
+    }
+
+    pub fn to_left(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+
+    pub fn to_right(&self, value: f64) -> f64 {
+        (value - self.offset) / self.scale
+    }
+
+    /// Axis on the right side of the plot that shows the values of the right axis
+    pub fn axis_hints(self, label: &str) -> AxisHints {
+        AxisHints::new_y()
+            .label(label)
+            .placement(HPlacement::Right)
+            .formatter(move |mark, _, _| format_value(self.to_right(mark.value)))
+    }
+}
+
+/// Formats a value with 4 significant digits
+pub fn format_value(value: f64) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{}", value);
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    if !(-4..6).contains(&magnitude) {
+        return format!("{:.3e}", value);
+    }
+    let decimals = (3 - magnitude).max(0) as usize;
+    let text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// Min and max of the y values
+pub fn y_range<'a>(points: impl IntoIterator<Item = &'a [f64; 2]>) -> Option<(f64, f64)> {
+    points
+        .into_iter()
+        .map(|p| p[1])
+        .filter(|y| y.is_finite())
+        .fold(None, |range, y| match range {
+            None => Some((y, y)),
+            Some((min, max)) => Some((f64::min(min, y), f64::max(max, y))),
+        })
+}
@@ -0,0 +1,8 @@
+//! Tools that are shared between the plots of different tab types
+pub mod annotations;
+pub mod cursors;
+pub mod filters;
+pub mod layout;
+pub mod spectrum;
+pub mod style;
+pub mod trajectory;
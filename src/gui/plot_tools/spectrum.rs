@@ -0,0 +1,235 @@
+use crate::signal::fft::{spectrogram, spectrum, Window};
+use crate::signal::filter::sample_rate;
+use egui::{Color32, ColorImage, DragValue, TextureHandle, TextureOptions, Ui};
+use egui_plot::{Legend, Line, Plot, PlotImage, PlotPoint, PlotPoints};
+
+/// Everything the cached spectrum depends on
+#[derive(Clone, PartialEq)]
+struct SpectrumKey {
+    column: String,
+    input: (usize, [f64; 2], [f64; 2]),
+    window: Window,
+    spectrogram: bool,
+    segment: usize,
+    overlap: f64,
+    x_per_second: f64,
+}
+
+enum SpectrumData {
+    Spectrum(Vec<[f64; 2]>),
+    /// the texture with the position of its center and its size in plot coordinates
+    Spectrogram(TextureHandle, [f64; 2], [f64; 2]),
+}
+
+/// Spectrum or spectrogram of one column over the visible part of the main plot
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SpectrumView {
+    pub enabled: bool,
+    pub column: String,
+    pub window: Window,
+    /// show a spectrogram (stft) instead of the spectrum over the whole visible window
+    pub spectrogram: bool,
+    /// samples per segment of the spectrogram
+    pub segment: usize,
+    /// fraction of a segment that overlaps with the next one
+    pub overlap: f64,
+    /// how many units of the x column make up one second, e.g. 1000 if x is in ms
+    pub x_per_second: f64,
+    #[serde(skip)]
+    cache: Option<(SpectrumKey, SpectrumData)>,
+}
+
+impl Default for SpectrumView {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            column: String::new(),
+            window: Window::Hann,
+            spectrogram: false,
+            segment: 256,
+            overlap: 0.5,
+            x_per_second: 1.0,
+            cache: None,
+        }
+    }
+}
+
+impl SpectrumView {
+    pub fn new(x_per_second: f64) -> Self {
+        Self {
+            x_per_second,
+            ..Default::default()
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, names: &[String]) {
+        ui.checkbox(&mut self.enabled, "Show spectrum");
+        ui.horizontal(|ui| {
+            ui.label("Column: ");
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(self.column.clone())
+                .show_ui(ui, |ui| {
+                    for name in names {
+                        ui.selectable_value(&mut self.column, name.clone(), name);
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Window: ");
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(self.window.to_string())
+                .show_ui(ui, |ui| {
+                    for window in Window::all() {
+                        ui.selectable_value(&mut self.window, window, window.to_string());
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("x units per second: ");
+            ui.add(DragValue::new(&mut self.x_per_second).clamp_range(1e-9..=1e9));
+        });
+        ui.checkbox(&mut self.spectrogram, "Spectrogram");
+        if self.spectrogram {
+            ui.horizontal(|ui| {
+                ui.label("Segment: ");
+                ui.add(DragValue::new(&mut self.segment).clamp_range(4..=65536));
+                ui.label("Overlap: ");
+                ui.add(
+                    DragValue::new(&mut self.overlap)
+                        .speed(0.01)
+                        .clamp_range(0.0..=0.95),
+                );
+            });
+        }
+    }
+
+    /// Draws the spectrum of `points` (the data of [`Self::column`]), only using the points inside `visible`
+    pub fn show(&mut self, ui: &mut Ui, points: &[[f64; 2]], visible: Option<(f64, f64)>) {
+        let points = match visible {
+            Some((min, max)) => points
+                .iter()
+                .filter(|p| p[0] >= min && p[0] <= max)
+                .copied()
+                .collect::<Vec<_>>(),
+            None => points.to_vec(),
+        };
+        let key = SpectrumKey {
+            column: self.column.clone(),
+            input: (
+                points.len(),
+                points.first().copied().unwrap_or_default(),
+                points.last().copied().unwrap_or_default(),
+            ),
+            window: self.window,
+            spectrogram: self.spectrogram,
+            segment: self.segment,
+            overlap: self.overlap,
+            x_per_second: self.x_per_second,
+        };
+        if self.cache.as_ref().map(|c| &c.0) != Some(&key) {
+            let data = self.compute(ui, &points);
+            self.cache = Some((key, data));
+        }
+        let data = &self.cache.as_ref().unwrap().1;
+        match data {
+            SpectrumData::Spectrum(spectrum) => {
+                Plot::new(ui.next_auto_id())
+                    .legend(Legend::default())
+                    .x_axis_label("frequency [Hz]")
+                    .y_axis_label("magnitude [dB]")
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(spectrum.clone()))
+                                .name(format!("{} ({})", self.column, self.window)),
+                        );
+                    });
+            }
+            SpectrumData::Spectrogram(texture, center, size) => {
+                Plot::new(ui.next_auto_id())
+                    .y_axis_label("frequency [Hz]")
+                    .show(ui, |plot_ui| {
+                        plot_ui.image(
+                            PlotImage::new(
+                                texture,
+                                PlotPoint::new(center[0], center[1]),
+                                [size[0] as f32, size[1] as f32],
+                            )
+                            .name(&self.column),
+                        );
+                    });
+            }
+        }
+    }
+
+    fn compute(&self, ui: &Ui, points: &[[f64; 2]]) -> SpectrumData {
+        let x = points.iter().map(|p| p[0]).collect::<Vec<_>>();
+        let y = points.iter().map(|p| p[1]).collect::<Vec<_>>();
+        let seconds = x.iter().map(|x| x / self.x_per_second).collect::<Vec<_>>();
+        let fs = sample_rate(&seconds);
+        if !self.spectrogram {
+            return SpectrumData::Spectrum(
+                spectrum(&y, fs, self.window)
+                    .into_iter()
+                    .map(|(f, db)| [f, db])
+                    .collect(),
+            );
+        }
+        let result = spectrogram(&x, &y, fs, self.window, self.segment, self.overlap, 1000);
+        let bins = result.values.first().map_or(0, |v| v.len());
+        let max = result
+            .values
+            .iter()
+            .flatten()
+            .fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+        // show a range of 80 dB below the loudest bin
+        let min = max - 80.0;
+        let mut image = ColorImage::new([result.values.len().max(1), bins.max(1)], Color32::BLACK);
+        for (column, values) in result.values.iter().enumerate() {
+            for (bin, db) in values.iter().enumerate() {
+                // the first row of the image is the highest frequency
+                let row = bins - 1 - bin;
+                image.pixels[row * result.values.len() + column] =
+                    heat_color(((db - min) / (max - min)) as f32);
+            }
+        }
+        let texture = ui
+            .ctx()
+            .load_texture("spectrogram", image, TextureOptions::NEAREST);
+        let (start, end) = match (result.times.first(), result.times.last()) {
+            (Some(start), Some(end)) => (*start, *end),
+            _ => (0.0, 0.0),
+        };
+        // every column of the image covers the time between two segment centers
+        let step = if result.times.len() > 1 {
+            (end - start) / (result.times.len() - 1) as f64
+        } else {
+            1.0
+        };
+        SpectrumData::Spectrogram(
+            texture,
+            [(start + end) / 2.0, result.max_frequency / 2.0],
+            [end - start + step, result.max_frequency],
+        )
+    }
+}
+
+/// Maps 0..1 to black - purple - red - yellow - white
+pub fn heat_color(t: f32) -> Color32 {
+    let stops = [
+        (0, 0, 0),
+        (80, 18, 123),
+        (220, 50, 40),
+        (250, 200, 40),
+        (255, 255, 255),
+    ];
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) } * (stops.len() - 1) as f32;
+    let i = (t as usize).min(stops.len() - 2);
+    let f = t - i as f32;
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * f) as u8;
+    Color32::from_rgb(
+        lerp(stops[i].0, stops[i + 1].0),
+        lerp(stops[i].1, stops[i + 1].1),
+        lerp(stops[i].2, stops[i + 1].2),
+    )
+}
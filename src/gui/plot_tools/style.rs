@@ -0,0 +1,399 @@
+use crate::export::{Series, SeriesShape};
+use egui::{Color32, DragValue, Ui};
+use egui_plot::{Bar, BarChart, BoxElem, BoxPlot, BoxSpread, Line, PlotPoints, PlotUi, Points};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Box plots group the points by their x value if there are at most this many different x values,
+/// otherwise the whole series is drawn as one box. The groups of the xy plot have the same limit.
+pub const MAX_CATEGORIES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ChartStyle {
+    Line,
+    Scatter,
+    Step,
+    Histogram,
+    Bar,
+    BoxPlot,
+}
+
+impl Display for ChartStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl ChartStyle {
+    pub fn all() -> [ChartStyle; 6] {
+        [
+            ChartStyle::Line,
+            ChartStyle::Scatter,
+            ChartStyle::Step,
+            ChartStyle::Histogram,
+            ChartStyle::Bar,
+            ChartStyle::BoxPlot,
+        ]
+    }
+}
+
+/// How a single series is drawn
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SeriesStyle {
+    pub style: ChartStyle,
+    /// overrides the default color of the series
+    pub color: Option<Color32>,
+    /// radius of the markers of scatter plots
+    pub marker_size: f32,
+    /// number of bins of histograms
+    pub bins: usize,
+    /// hidden series aren't drawn or exported
+    pub hidden: bool,
+}
+
+impl Default for SeriesStyle {
+    fn default() -> Self {
+        SeriesStyle {
+            style: ChartStyle::Line,
+            color: None,
+            marker_size: 2.0,
+            bins: 20,
+            hidden: false,
+        }
+    }
+}
+
+impl SeriesStyle {
+    pub fn color(&self, default: Color32) -> Color32 {
+        self.color.unwrap_or(default)
+    }
+
+    fn ui(&mut self, ui: &mut Ui, name: &str, default_color: Color32) {
+        ui.horizontal(|ui| {
+            let mut visible = !self.hidden;
+            if ui.checkbox(&mut visible, "").on_hover_text("Show the series").changed() {
+                self.hidden = !visible;
+            }
+            let mut color = self.color(default_color);
+            if ui.color_edit_button_srgba(&mut color).changed() {
+                self.color = Some(color);
+            }
+            if self.color.is_some() && ui.small_button("x").on_hover_text("Reset color").clicked() {
+                self.color = None;
+            }
+            ui.label(name);
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(self.style.to_string())
+                .show_ui(ui, |ui| {
+                    for style in ChartStyle::all() {
+                        ui.selectable_value(&mut self.style, style, style.to_string());
+                    }
+                });
+            match self.style {
+                ChartStyle::Scatter => {
+                    ui.add(
+                        DragValue::new(&mut self.marker_size)
+                            .speed(0.1)
+                            .clamp_range(0.1..=20.0)
+                            .prefix("size: "),
+                    );
+                }
+                ChartStyle::Histogram => {
+                    ui.add(DragValue::new(&mut self.bins).clamp_range(1..=1000).prefix("bins: "));
+                }
+                _ => {}
+            }
+        });
+    }
+
+    /// Draws the points in this style
+    pub fn draw(&self, plot_ui: &mut PlotUi, name: &str, points: Vec<[f64; 2]>, color: Color32) {
+        match self.style {
+            ChartStyle::Line => {
+                plot_ui.line(Line::new(PlotPoints::from(points)).color(color).name(name));
+            }
+            ChartStyle::Scatter => {
+                plot_ui.points(
+                    Points::new(PlotPoints::from(points))
+                        .color(color)
+                        .radius(self.marker_size)
+                        .name(name),
+                );
+            }
+            ChartStyle::Step => {
+                plot_ui.line(Line::new(PlotPoints::from(steps(&points))).color(color).name(name));
+            }
+            ChartStyle::Histogram => {
+                let values = points.iter().map(|p| p[1]).collect::<Vec<_>>();
+                let (bins, width) = histogram(&values, self.bins);
+                let bars = bins
+                    .into_iter()
+                    .map(|(center, count)| Bar::new(center, count as f64).width(width))
+                    .collect();
+                plot_ui.bar_chart(BarChart::new(bars).color(color).name(name));
+            }
+            ChartStyle::Bar => {
+                let width = min_spacing(points.iter().map(|p| p[0])) * 0.8;
+                let bars = points
+                    .iter()
+                    .map(|p| Bar::new(p[0], p[1]).width(width))
+                    .collect();
+                plot_ui.bar_chart(BarChart::new(bars).color(color).name(name));
+            }
+            ChartStyle::BoxPlot => {
+                let categories = box_categories(&points);
+                let width = min_spacing(categories.iter().map(|c| c.0)) * 0.6;
+                let boxes = categories
+                    .into_iter()
+                    .filter_map(|(x, values)| {
+                        box_spread(values).map(|spread| BoxElem::new(x, spread).box_width(width))
+                    })
+                    .collect();
+                plot_ui.box_plot(BoxPlot::new(boxes).color(color).name(name));
+            }
+        }
+    }
+
+    /// The points in this style for exporting the plot, like [`Self::draw`]
+    pub fn export_series(&self, name: &str, points: Vec<[f64; 2]>, color: Color32) -> Series {
+        let shape = match self.style {
+            ChartStyle::Line => SeriesShape::Line(points),
+            ChartStyle::Scatter => SeriesShape::Points(points, self.marker_size),
+            ChartStyle::Step => SeriesShape::Line(steps(&points)),
+            ChartStyle::Histogram => {
+                let values = points.iter().map(|p| p[1]).collect::<Vec<_>>();
+                let (bins, width) = histogram(&values, self.bins);
+                let bars = bins
+                    .into_iter()
+                    .map(|(center, count)| [center, count as f64])
+                    .collect();
+                SeriesShape::Bars(bars, width)
+            }
+            ChartStyle::Bar => {
+                let width = min_spacing(points.iter().map(|p| p[0])) * 0.8;
+                SeriesShape::Bars(points, width)
+            }
+            ChartStyle::BoxPlot => {
+                let categories = box_categories(&points);
+                let width = min_spacing(categories.iter().map(|c| c.0)) * 0.6;
+                let boxes = categories
+                    .into_iter()
+                    .filter_map(|(x, values)| {
+                        let s = box_spread(values)?;
+                        Some((x, [s.lower_whisker, s.quartile1, s.median, s.quartile3, s.upper_whisker]))
+                    })
+                    .collect();
+                SeriesShape::Boxes(boxes, width)
+            }
+        };
+        Series {
+            name: name.to_string(),
+            color,
+            shape,
+        }
+    }
+}
+
+/// Groups the y values by their x value for box plots
+fn box_categories(points: &[[f64; 2]]) -> Vec<(f64, Vec<f64>)> {
+    let mut categories: Vec<(f64, Vec<f64>)> = vec![];
+    for p in points {
+        match categories.iter_mut().find(|c| c.0 == p[0]) {
+            Some(category) => category.1.push(p[1]),
+            None => {
+                if categories.len() > MAX_CATEGORIES {
+                    break;
+                }
+                categories.push((p[0], vec![p[1]]));
+            }
+        }
+    }
+    if categories.len() > MAX_CATEGORIES {
+        // too many different x values to be categories, use the middle of the series
+        let center = points.iter().map(|p| p[0]).sum::<f64>() / points.len() as f64;
+        categories = vec![(center, points.iter().map(|p| p[1]).collect())];
+    }
+    categories
+}
+
+/// Plot in which one column is plotted against another one, e.g. the red value against the green value
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct XYScatter {
+    pub enabled: bool,
+    pub x: String,
+    pub y: String,
+    /// the points are colored by the value of this column, if it isn't empty
+    pub group: String,
+    /// names of the values of the group column, e.g. 0 = "Black"
+    pub group_names: Vec<String>,
+    pub marker_size: f32,
+}
+
+impl Default for XYScatter {
+    fn default() -> Self {
+        XYScatter {
+            enabled: false,
+            x: String::new(),
+            y: String::new(),
+            group: String::new(),
+            group_names: vec![],
+            marker_size: 1.0,
+        }
+    }
+}
+
+impl XYScatter {
+    /// Name of the group with the value `value`
+    pub fn group_name(&self, value: f32) -> String {
+        if value >= 0.0 && value.fract() == 0.0 {
+            if let Some(name) = self.group_names.get(value as usize) {
+                return name.clone();
+            }
+        }
+        format!("{} = {}", self.group, value)
+    }
+}
+
+/// The chart styles of all series of a plot, keyed by the name of the series
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct ChartStyles {
+    pub series: HashMap<String, SeriesStyle>,
+    pub xy: XYScatter,
+}
+
+impl ChartStyles {
+    pub fn get(&self, name: &str) -> SeriesStyle {
+        self.series.get(name).cloned().unwrap_or_default()
+    }
+
+    /// `series` are the names and default colors of the series, `columns` are all columns the xy plot can use
+    pub fn ui(&mut self, ui: &mut Ui, series: &[(String, Color32)], columns: &[String]) {
+        ui.checkbox(&mut self.xy.enabled, "X-Y scatter");
+        if self.xy.enabled {
+            let column_combo = |ui: &mut Ui, label: &str, value: &mut String, none: bool| {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    egui::ComboBox::from_id_source(ui.next_auto_id())
+                        .selected_text(value.clone())
+                        .show_ui(ui, |ui| {
+                            if none {
+                                ui.selectable_value(value, String::new(), "none");
+                            }
+                            for column in columns {
+                                ui.selectable_value(value, column.clone(), column);
+                            }
+                        });
+                });
+            };
+            column_combo(ui, "X: ", &mut self.xy.x, false);
+            column_combo(ui, "Y: ", &mut self.xy.y, false);
+            column_combo(ui, "Color by: ", &mut self.xy.group, true);
+            ui.add(
+                DragValue::new(&mut self.xy.marker_size)
+                    .speed(0.1)
+                    .clamp_range(0.1..=20.0)
+                    .prefix("marker size: "),
+            );
+            return;
+        }
+        for (name, color) in series {
+            let mut style = self.get(name);
+            style.ui(ui, name, *color);
+            if style != SeriesStyle::default() {
+                self.series.insert(name.clone(), style);
+            } else {
+                self.series.remove(name);
+            }
+        }
+    }
+}
+
+/// Turns the points into a staircase, every value is held until the next point
+pub fn steps(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut result = Vec::with_capacity(points.len() * 2);
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            result.push([point[0], points[i - 1][1]]);
+        }
+        result.push(*point);
+    }
+    result
+}
+
+/// Counts the values in `bins` evenly spaced bins, returns the centers and counts of the bins and the bin width
+pub fn histogram(values: &[f64], bins: usize) -> (Vec<(f64, usize)>, f64) {
+    let bins = bins.max(1);
+    let values = values.iter().filter(|v| v.is_finite()).collect::<Vec<_>>();
+    if values.is_empty() {
+        return (vec![], 1.0);
+    }
+    let min = values.iter().fold(f64::INFINITY, |a, b| a.min(**b));
+    let max = values.iter().fold(f64::NEG_INFINITY, |a, b| a.max(**b));
+    let width = if max > min {
+        (max - min) / bins as f64
+    } else {
+        1.0
+    };
+    let mut counts = vec![0; bins];
+    for value in values {
+        let bin = (((value - min) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    (
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (min + (i as f64 + 0.5) * width, count))
+            .collect(),
+        width,
+    )
+}
+
+/// Quartiles and whiskers (1.5 times the interquartile range) of the values
+pub fn box_spread(mut values: Vec<f64>) -> Option<BoxSpread> {
+    values.retain(|v| v.is_finite());
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let quantile = |q: f64| {
+        let position = q * (values.len() - 1) as f64;
+        let lower = values[position.floor() as usize];
+        let upper = values[position.ceil() as usize];
+        lower + (upper - lower) * position.fract()
+    };
+    let (q1, median, q3) = (quantile(0.25), quantile(0.5), quantile(0.75));
+    let iqr = q3 - q1;
+    let low = values
+        .iter()
+        .copied()
+        .find(|v| *v >= q1 - 1.5 * iqr)
+        .unwrap_or(q1);
+    let high = values
+        .iter()
+        .rev()
+        .copied()
+        .find(|v| *v <= q3 + 1.5 * iqr)
+        .unwrap_or(q3);
+    Some(BoxSpread::new(low, q1, median, q3, high))
+}
+
+/// Smallest distance between two different values, 1 if there aren't two different values
+fn min_spacing(values: impl Iterator<Item = f64>) -> f64 {
+    let mut values = values.filter(|v| v.is_finite()).collect::<Vec<_>>();
+    values.sort_by(|a, b| a.total_cmp(b));
+    let spacing = values
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| *d > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    if spacing.is_finite() {
+        spacing
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,249 @@
+use crate::gui::plot_tools::spectrum::heat_color;
+use crate::signal::odometry::{distance_step, pose_at, speed_step, Odometry, Pairing, Pose};
+use egui::{Color32, DragValue, Ui};
+use egui_plot::{Arrows, Legend, Line, MarkerShape, Plot, PlotPoints, Points};
+
+/// the path is drawn in this many pieces of different colors
+const GRADIENT_STEPS: usize = 64;
+/// at most this many poses are drawn, the others are skipped
+const MAX_POSES: usize = 4096;
+const MARKER_COLOR: Color32 = Color32::from_rgb(0, 200, 255);
+
+/// What the values of the wheels are
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum WheelInput {
+    /// distance driven since the start, like `DrivenDistance`
+    Distances,
+    /// distance per second, like `RealSpeeds`
+    Speeds,
+}
+
+/// The settings the cached poses depend on
+#[derive(Clone, PartialEq)]
+struct TrajectoryKey {
+    wheel_input: WheelInput,
+    wheel_base: f64,
+    scale: f64,
+    x_per_second: f64,
+}
+
+/// The poses of the samples so far, new samples of the wheels are added to them
+struct TrajectoryCache {
+    key: TrajectoryKey,
+    /// number of samples of the right and the left wheel and the last of them,
+    /// the samples of the next frame have to continue them
+    len: [usize; 2],
+    last: [Option<[f64; 2]>; 2],
+    pairing: Pairing,
+    odometry: Odometry,
+}
+
+/// The path of a differential drive robot, integrated from the values of its right and left wheel
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TrajectoryView {
+    pub enabled: bool,
+    pub right: String,
+    pub left: String,
+    pub wheel_input: WheelInput,
+    /// distance between the wheels, in the unit of the scaled distances
+    pub wheel_base: f64,
+    /// multiplied with the values of the wheels, e.g. the distance per encoder tick
+    pub scale: f64,
+    /// how many units of the x column make up one second, only used for speeds
+    pub x_per_second: f64,
+    #[serde(skip)]
+    cache: Option<TrajectoryCache>,
+    /// identifies the samples the cache was computed from, see [`TrajectoryView::set_input`]
+    #[serde(skip)]
+    input: Option<[u64; 2]>,
+}
+
+impl Default for TrajectoryView {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            right: "right distance".to_string(),
+            left: "left distance".to_string(),
+            wheel_input: WheelInput::Distances,
+            wheel_base: 15.0,
+            scale: 1.0,
+            x_per_second: 1000.0,
+            cache: None,
+            input: None,
+        }
+    }
+}
+
+impl TrajectoryView {
+    pub fn ui(&mut self, ui: &mut Ui, names: &[String]) {
+        ui.checkbox(&mut self.enabled, "Show trajectory");
+        for (label, column) in [("Right wheel: ", &mut self.right), ("Left wheel: ", &mut self.left)] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                egui::ComboBox::from_id_source(ui.next_auto_id())
+                    .selected_text(column.clone())
+                    .show_ui(ui, |ui| {
+                        for name in names {
+                            ui.selectable_value(column, name.clone(), name);
+                        }
+                    });
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.wheel_input, WheelInput::Distances, "Distances");
+            ui.radio_value(&mut self.wheel_input, WheelInput::Speeds, "Speeds");
+        });
+        egui::Grid::new(ui.next_auto_id()).num_columns(2).show(ui, |ui| {
+            ui.label("Wheel base");
+            ui.add(DragValue::new(&mut self.wheel_base).speed(0.1).clamp_range(1e-6..=1e9))
+                .on_hover_text("Distance between the wheels, in the unit of the scaled values");
+            ui.end_row();
+            ui.label("Encoder scale");
+            ui.add(DragValue::new(&mut self.scale).speed(0.01))
+                .on_hover_text("The values of the wheels are multiplied with it");
+            ui.end_row();
+            if self.wheel_input == WheelInput::Speeds {
+                ui.label("x units per second");
+                ui.add(DragValue::new(&mut self.x_per_second).clamp_range(1e-9..=1e9));
+                ui.end_row();
+            }
+        });
+    }
+
+    /// Forgets the poses, e.g. when the samples started again
+    pub fn clear(&mut self) {
+        self.cache = None;
+    }
+
+    /// For samples that are given as a whole every frame: the poses are computed again when `input`,
+    /// e.g. the hashes of the right and the left samples, changes. Samples that only grow don't need it.
+    pub fn set_input(&mut self, input: [u64; 2]) {
+        if self.input.replace(input) != Some(input) {
+            self.clear();
+        }
+    }
+
+    /// The poses of the samples, only the samples that were added since the last frame are integrated.
+    /// `point` gives the time and the value of a sample.
+    fn poses<P: Copy>(&mut self, right: &[P], left: &[P], point: impl Fn(P) -> [f64; 2]) -> &[Pose] {
+        let key = TrajectoryKey {
+            wheel_input: self.wheel_input,
+            wheel_base: self.wheel_base,
+            scale: self.scale,
+            x_per_second: self.x_per_second,
+        };
+        let last = [right.last(), left.last()].map(|p| p.map(|p| point(*p)));
+        let continues = self.cache.as_ref().is_some_and(|c| {
+            c.key == key
+                && [right, left].iter().zip(c.len).zip(c.last).all(|((samples, len), last)| {
+                    len <= samples.len() && len.checked_sub(1).map(|i| point(samples[i])) == last
+                })
+        });
+        if !continues {
+            self.cache = Some(TrajectoryCache {
+                key,
+                len: [0, 0],
+                last: [None, None],
+                pairing: Pairing::default(),
+                odometry: Odometry::default(),
+            });
+        }
+        let cache = self.cache.as_mut().unwrap();
+        if cache.len != [right.len(), left.len()] {
+            let samples = cache.pairing.pair(right, left, &point);
+            let (scale, x_per_second) = (self.scale, self.x_per_second);
+            match self.wheel_input {
+                WheelInput::Distances => {
+                    cache.odometry.extend(&samples, |a, b| distance_step(a, b, scale), self.wheel_base)
+                }
+                WheelInput::Speeds => cache.odometry.extend(
+                    &samples,
+                    |a, b| speed_step(a, b, scale, x_per_second),
+                    self.wheel_base,
+                ),
+            }
+            cache.len = [right.len(), left.len()];
+            cache.last = last;
+        }
+        &cache.odometry.poses
+    }
+
+    /// Draws the path from the samples of [`Self::right`] and [`Self::left`] with a marker at the time `cursor`.
+    /// `point` gives the time and the value of a sample.
+    pub fn show<P: Copy>(
+        &mut self,
+        ui: &mut Ui,
+        right: &[P],
+        left: &[P],
+        point: impl Fn(P) -> [f64; 2],
+        cursor: Option<f64>,
+    ) {
+        let empty = format!("No values of \"{}\" and \"{}\"", self.right, self.left);
+        let wheel_base = self.wheel_base;
+        let poses = self.poses(right, left, point);
+        let (Some(first), Some(last)) = (poses.first(), poses.last()) else {
+            ui.label(empty);
+            return;
+        };
+        let duration = last.t - first.t;
+        // purple at the start, yellow at the end
+        let color = |t: f64| {
+            let f = if duration > 0.0 { (t - first.t) / duration } else { 1.0 };
+            heat_color(0.25 + 0.55 * f as f32)
+        };
+        ui.label(format!("Time from {:.0} (purple) to {:.0} (yellow)", first.t, last.t));
+        let stride = poses.len() / MAX_POSES + 1;
+        let mut drawn = poses.iter().step_by(stride).copied().collect::<Vec<_>>();
+        if drawn.last() != Some(last) {
+            drawn.push(*last);
+        }
+        let extent = drawn
+            .iter()
+            .map(|p| p.x.abs().max(p.y.abs()))
+            .fold(wheel_base, f64::max);
+        let marker = cursor.and_then(|t| pose_at(poses, t));
+        Plot::new(ui.next_auto_id())
+            .legend(Legend::default())
+            .data_aspect(1.0)
+            .x_axis_label("x")
+            .y_axis_label("y")
+            .show(ui, |plot_ui| {
+                let piece = drawn.len().div_ceil(GRADIENT_STEPS).max(1);
+                let mut start = 0;
+                while start + 1 < drawn.len() {
+                    // the pieces share their last point with the next one, so the path has no gaps
+                    let end = (start + piece).min(drawn.len() - 1);
+                    let points = drawn[start..=end].iter().map(|p| [p.x, p.y]).collect::<Vec<_>>();
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(points))
+                            .color(color(drawn[start].t))
+                            .width(2.0),
+                    );
+                    start = end;
+                }
+                plot_ui.points(
+                    Points::new([first.x, first.y])
+                        .shape(MarkerShape::Square)
+                        .radius(4.0)
+                        .color(color(first.t))
+                        .name("start"),
+                );
+                if let Some(pose) = marker {
+                    let length = extent * 0.05;
+                    let tip = [pose.x + length * pose.heading.cos(), pose.y + length * pose.heading.sin()];
+                    plot_ui.points(
+                        Points::new([pose.x, pose.y])
+                            .radius(5.0)
+                            .color(MARKER_COLOR)
+                            .name(format!("t = {:.0}", pose.t)),
+                    );
+                    plot_ui.arrows(
+                        Arrows::new(vec![[pose.x, pose.y]], vec![tip])
+                            .color(MARKER_COLOR)
+                            .name(format!("t = {:.0}", pose.t)),
+                    );
+                }
+            });
+    }
+}
@@ -0,0 +1,374 @@
+use crate::export::{Figure, Panel, Series, SeriesShape};
+use crate::gui::plot_tools::layout::format_value;
+use crate::gui::tab_types::overlay::Run;
+use crate::gui::tab_types::plot_file::formats;
+use crate::gui::tab_types::plot_file::get_color;
+use crate::gui::tab_types::TabStruct;
+use crate::signal::compare::{common_grid, diff, DiffStats};
+use egui::{CollapsingHeader, Color32, DragValue, Ui};
+use egui_plot::{HLine, Legend, Line, Plot, PlotPoints};
+use std::collections::HashMap;
+
+/// What the results were computed for: golden file, candidate file, x column and grid step
+type CompareKey = (String, String, String, f64);
+
+/// The comparison of one column of the two runs
+struct ColumnResult {
+    column: String,
+    /// candidate - golden on the common grid
+    difference: Vec<[f64; 2]>,
+    /// None if the candidate doesn't have the column or the runs don't overlap
+    stats: Option<DiffStats>,
+    missing: bool,
+}
+
+/// Compares a run column by column against a golden run with the same columns,
+/// e.g. a recording of new firmware against a known good one
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Compare {
+    pub golden: Run,
+    pub candidate: Run,
+    /// empty to use the row index
+    pub x_column: String,
+    /// spacing of the common grid, 0 to use the spacing of the denser run
+    pub step: f64,
+    /// largest allowed deviation of columns without their own tolerance
+    pub tolerance: f64,
+    pub tolerances: HashMap<String, f64>,
+    /// column shown in the plot
+    pub column: String,
+    show_interface: bool,
+    #[serde(skip)]
+    results: Option<(CompareKey, Vec<ColumnResult>)>,
+}
+
+impl Default for Compare {
+    fn default() -> Self {
+        Compare {
+            golden: Run::default(),
+            candidate: Run::default(),
+            x_column: String::new(),
+            step: 0.0,
+            tolerance: 0.01,
+            tolerances: HashMap::new(),
+            column: String::new(),
+            show_interface: true,
+            results: None,
+        }
+    }
+}
+
+#[typetag::serde]
+impl TabStruct for Compare {
+    fn interface(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Golden run…").clicked() {
+                if let Some(path) = pick_file() {
+                    self.golden = Run::new(path);
+                    self.results = None;
+                }
+            }
+            if ui.button("Candidate run…").clicked() {
+                if let Some(path) = pick_file() {
+                    self.candidate = Run::new(path);
+                    self.results = None;
+                }
+            }
+            if ui.button("Swap").clicked() {
+                std::mem::swap(&mut self.golden, &mut self.candidate);
+                self.results = None;
+            }
+            if ui.button("Reload").clicked() {
+                self.golden.load();
+                self.candidate.load();
+                self.results = None;
+            }
+        });
+        for (label, run) in [("Golden: ", &self.golden), ("Candidate: ", &self.candidate)] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.label(run.short_name()).on_hover_text(&run.file_name);
+            });
+            if let Some(error) = run.error() {
+                ui.colored_label(Color32::RED, error);
+            }
+        }
+        let header = self.golden.header().to_vec();
+        if !self.candidate.header().is_empty() && self.candidate.header() != header.as_slice() {
+            ui.colored_label(Color32::YELLOW, "The columns of the runs differ");
+        }
+        ui.horizontal(|ui| {
+            ui.label("X axis: ");
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(if self.x_column.is_empty() {
+                    "index".to_string()
+                } else {
+                    self.x_column.clone()
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.x_column, String::new(), "index");
+                    for column in &header {
+                        ui.selectable_value(&mut self.x_column, column.clone(), column);
+                    }
+                });
+        });
+        ui.add(
+            DragValue::new(&mut self.step)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX)
+                .prefix("grid step: "),
+        )
+        .on_hover_text("Spacing of the common x grid, 0 uses the spacing of the denser run");
+        ui.add(
+            DragValue::new(&mut self.tolerance)
+                .speed(0.001)
+                .clamp_range(0.0..=f64::MAX)
+                .prefix("default tolerance: "),
+        )
+        .on_hover_text("Largest allowed deviation from the golden run");
+        ui.separator();
+        self.update_results();
+        self.results_ui(ui);
+    }
+
+    fn show_interface(&mut self) -> bool {
+        self.show_interface
+    }
+
+    fn title_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(self.title());
+            ui.checkbox(&mut self.show_interface, "Show Interface")
+        });
+    }
+
+    fn plot(&mut self, ui: &mut Ui) {
+        self.update_results();
+        let (runs, difference) = self.panels();
+        let tolerance = self.column_tolerance(&self.column);
+        let height = (ui.available_height() - ui.spacing().item_spacing.y) / 2.0;
+        let link = ui.id().with("compare");
+        Plot::new(ui.next_auto_id())
+            .legend(Legend::default())
+            .height(height)
+            .link_axis(link, true, false)
+            .link_cursor(link, true, false)
+            .show(ui, |plot_ui| {
+                for series in runs.series {
+                    if let SeriesShape::Line(points) = series.shape {
+                        plot_ui.line(Line::new(PlotPoints::from(points)).color(series.color).name(series.name));
+                    }
+                }
+            });
+        Plot::new(ui.next_auto_id())
+            .legend(Legend::default())
+            .height(height)
+            .link_axis(link, true, false)
+            .link_cursor(link, true, false)
+            .show(ui, |plot_ui| {
+                for series in difference.series {
+                    if let SeriesShape::Line(points) = series.shape {
+                        plot_ui.line(Line::new(PlotPoints::from(points)).color(series.color).name(series.name));
+                    }
+                }
+                for y in [tolerance, -tolerance] {
+                    plot_ui.hline(HLine::new(y).color(Color32::GRAY).name("tolerance"));
+                }
+            });
+    }
+
+    fn figure(&mut self) -> Option<Figure> {
+        self.update_results();
+        let (runs, difference) = self.panels();
+        Some(Figure {
+            title: self.title(),
+            x_label: if self.x_column.is_empty() {
+                "index".to_string()
+            } else {
+                self.x_column.clone()
+            },
+            x_range: None,
+            panels: vec![runs, difference],
+        })
+    }
+
+    fn title(&self) -> String {
+        format!("Compare: {}", self.column)
+    }
+}
+
+impl Compare {
+    fn key(&self) -> CompareKey {
+        (
+            self.golden.file_name.clone(),
+            self.candidate.file_name.clone(),
+            self.x_column.clone(),
+            self.step,
+        )
+    }
+
+    fn column_tolerance(&self, column: &str) -> f64 {
+        self.tolerances.get(column).copied().unwrap_or(self.tolerance)
+    }
+
+    /// Compares every column of the golden run, only if the runs or the settings changed
+    fn update_results(&mut self) {
+        self.golden.ensure_loaded();
+        self.candidate.ensure_loaded();
+        let key = self.key();
+        if self.results.as_ref().is_some_and(|(k, _)| *k == key) {
+            return;
+        }
+        let x_column = (!self.x_column.is_empty()).then_some(self.x_column.as_str());
+        let columns = self
+            .golden
+            .header()
+            .iter()
+            .filter(|c| **c != self.x_column)
+            .cloned()
+            .collect::<Vec<_>>();
+        // the grid comes from the x values, the first column only decides where they are valid
+        let x_values = |run: &Run| match columns.first() {
+            Some(column) => run.series(x_column, column),
+            None => vec![],
+        };
+        let grid = common_grid(&x_values(&self.golden), &x_values(&self.candidate), self.step);
+        let results = columns
+            .into_iter()
+            .map(|column| {
+                if !self.candidate.header().contains(&column) {
+                    return ColumnResult {
+                        column,
+                        difference: vec![],
+                        stats: None,
+                        missing: true,
+                    };
+                }
+                let golden = self.golden.series(x_column, &column);
+                let candidate = self.candidate.series(x_column, &column);
+                let (difference, stats) = diff(&golden, &candidate, &grid);
+                ColumnResult {
+                    column,
+                    difference,
+                    stats,
+                    missing: false,
+                }
+            })
+            .collect::<Vec<_>>();
+        if !results.iter().any(|r| r.column == self.column) {
+            self.column = results.first().map(|r| r.column.clone()).unwrap_or_default();
+        }
+        self.results = Some((key, results));
+    }
+
+    /// The table with the metrics of every column, columns out of tolerance are marked
+    fn results_ui(&mut self, ui: &mut Ui) {
+        let Some((_, results)) = &self.results else {
+            return;
+        };
+        let failed = results
+            .iter()
+            .filter(|r| r.missing || r.stats.is_some_and(|s| s.max_deviation > self.column_tolerance(&r.column)))
+            .count();
+        if results.is_empty() {
+            ui.label("Choose a golden and a candidate run");
+            return;
+        } else if failed == 0 {
+            ui.colored_label(Color32::GREEN, format!("All {} columns are within the tolerance", results.len()));
+        } else {
+            ui.colored_label(
+                Color32::RED,
+                format!("{} of {} columns are out of tolerance or missing", failed, results.len()),
+            );
+        }
+        CollapsingHeader::new("Columns").default_open(true).show(ui, |ui| {
+            egui::Grid::new(ui.next_auto_id()).striped(true).show(ui, |ui| {
+                for header in ["column", "rmse", "max deviation", "at", "lag", "correlation", "tolerance", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for result in results {
+                    ui.selectable_value(&mut self.column, result.column.clone(), &result.column);
+                    let mut tolerance = self.tolerances.get(&result.column).copied().unwrap_or(self.tolerance);
+                    match result.stats {
+                        Some(stats) => {
+                            ui.label(format_value(stats.rmse));
+                            ui.label(format_value(stats.max_deviation));
+                            ui.label(format_value(stats.max_at));
+                            ui.label(format_value(stats.lag));
+                            ui.label(format!("{:.3}", stats.correlation));
+                        }
+                        None => {
+                            for _ in 0..5 {
+                                ui.label("-");
+                            }
+                        }
+                    }
+                    if ui
+                        .add(DragValue::new(&mut tolerance).speed(0.001).clamp_range(0.0..=f64::MAX))
+                        .changed()
+                    {
+                        self.tolerances.insert(result.column.clone(), tolerance);
+                    }
+                    match result.stats {
+                        _ if result.missing => ui.colored_label(Color32::YELLOW, "missing"),
+                        Some(stats) if stats.max_deviation > tolerance => ui.colored_label(Color32::RED, "fail"),
+                        Some(_) => ui.colored_label(Color32::GREEN, "ok"),
+                        None => ui.colored_label(Color32::YELLOW, "no overlap"),
+                    };
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    /// Both runs of the selected column and their difference
+    fn panels(&self) -> (Panel, Panel) {
+        let x_column = (!self.x_column.is_empty()).then_some(self.x_column.as_str());
+        let line = |name: String, color: Color32, points: Vec<[f64; 2]>| Series {
+            name,
+            color,
+            shape: SeriesShape::Line(points),
+        };
+        let runs = Panel {
+            series: vec![
+                line(
+                    format!("{} (golden)", self.golden.short_name()),
+                    get_color(1),
+                    self.golden.series(x_column, &self.column),
+                ),
+                line(
+                    format!("{} (candidate)", self.candidate.short_name()),
+                    get_color(2),
+                    self.candidate.series(x_column, &self.column),
+                ),
+            ],
+            ..Default::default()
+        };
+        let difference = self
+            .results
+            .as_ref()
+            .and_then(|(_, results)| results.iter().find(|r| r.column == self.column))
+            .map(|r| r.difference.clone())
+            .unwrap_or_default();
+        let difference = Panel {
+            series: vec![line("candidate - golden".to_string(), get_color(3), difference)],
+            ..Default::default()
+        };
+        (runs, difference)
+    }
+}
+
+fn pick_file() -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        rfd::FileDialog::new()
+            .add_filter("data", &formats::extensions())
+            .add_filter("all files", &["*"])
+            .pick_file()
+            .map(|path| path.display().to_string())
+    }
+    #[cfg(target_arch = "wasm32")]
+    None
+}
@@ -1,8 +1,11 @@
-use crate::gui::tab_types::all_colors::AllColorsPlot;
+use crate::export::Figure;
+use crate::gui::tab_types::all_colors::all_colors_plot;
+use crate::gui::tab_types::compare::Compare;
 use crate::gui::tab_types::geometry::Geometry;
 use crate::gui::tab_types::image::ImageTab;
 
 use crate::gui::tab_types::neural_network::NeuralNetworkPlot;
+use crate::gui::tab_types::overlay::Overlay;
 use crate::gui::tab_types::plot_file::PlotFile;
 use crate::gui::tab_types::plotter::Plotter;
 use egui::Ui;
@@ -11,10 +14,12 @@ use std::fmt::{Debug, Display, Formatter};
 use strum_macros::EnumIter;
 use std::any::Any;
 pub mod all_colors;
+pub mod compare;
 pub mod geometry;
 pub mod image;
 
 pub mod neural_network;
+pub mod overlay;
 pub mod plot_file;
 pub mod plotter;
 pub mod tcp_client;
@@ -28,6 +33,8 @@ pub enum PlotType {
     Geometry,
     Plotter,
     TCPClient,
+    Overlay,
+    Compare,
 }
 
 impl Display for PlotType {
@@ -58,16 +65,22 @@ pub trait TabStruct: Any {
     fn get_file_path(&self) -> Option<String> {
         None
     }
+    /// The series of the plot as they are shown, for exporting them as an image
+    fn figure(&mut self) -> Option<Figure> {
+        None
+    }
 }
 
 pub fn default_plot(plot_type: PlotType) -> Box<dyn TabStruct> {
     match plot_type {
-        PlotType::AllColors => Box::<AllColorsPlot>::default(),
+        PlotType::AllColors => Box::new(all_colors_plot()),
         PlotType::NeuralNetwork => Box::<NeuralNetworkPlot>::default(),
         PlotType::Other => Box::<PlotFile>::default(),
         PlotType::Image => Box::<ImageTab>::default(),
         PlotType::Geometry => Box::<Geometry>::default(),
         PlotType::Plotter => Box::<Plotter>::default(),
         PlotType::TCPClient => Box::<tcp_client::TCPClient>::default(),
+        PlotType::Overlay => Box::<Overlay>::default(),
+        PlotType::Compare => Box::<Compare>::default(),
     }
 }
@@ -0,0 +1,314 @@
+use crate::gui::tab_types::plot_file::get_color;
+use crate::gui::tab_types::plot_file::formats::read_table;
+use crate::gui::tab_types::plot_file::table::Table;
+use crate::gui::tab_types::TabStruct;
+use crate::signal::align::{first_crossing, Edge};
+use egui::{CollapsingHeader, Color32, DragValue, Ui};
+use egui_plot::{Legend, Line, Plot, PlotPoints, VLine};
+
+/// One recorded run, a csv file with the same columns as the other runs
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Run {
+    pub file_name: String,
+    pub enabled: bool,
+    /// added to the x values after the alignment
+    pub offset: f64,
+    pub color: Option<Color32>,
+    #[serde(skip)]
+    table: Option<Table>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for Run {
+    fn default() -> Self {
+        Run {
+            file_name: String::new(),
+            enabled: true,
+            offset: 0.0,
+            color: None,
+            table: None,
+            error: None,
+        }
+    }
+}
+
+impl Run {
+    pub fn new(file_name: String) -> Self {
+        let mut run = Run {
+            file_name,
+            ..Default::default()
+        };
+        run.load();
+        run
+    }
+
+    pub fn load(&mut self) {
+        match read_table(&self.file_name) {
+            Ok(table) => {
+                self.error = table.parse_error.clone();
+                self.table = Some(table);
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.table = None;
+            }
+        }
+    }
+
+    /// Last part of the path, used for the legend
+    pub fn short_name(&self) -> String {
+        self.file_name
+            .replace('\\', "/")
+            .rsplit('/')
+            .next()
+            .unwrap_or("")
+            .to_string()
+    }
+
+    /// Columns of the file, empty if it couldn't be loaded
+    pub fn header(&self) -> &[String] {
+        self.table.as_ref().map(|t| t.header.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Loads the file if that wasn't tried yet, the tables aren't saved with the tab
+    pub fn ensure_loaded(&mut self) {
+        if self.table.is_none() && self.error.is_none() && !self.file_name.is_empty() {
+            self.load();
+        }
+    }
+
+    /// The x values of the rows (the row index if `x_column` is None) and the values of `column`
+    pub fn series(&self, x_column: Option<&str>, column: &str) -> Vec<[f64; 2]> {
+        let Some(table) = &self.table else {
+            return vec![];
+        };
+        let Some(y) = table.column(column) else {
+            return vec![];
+        };
+        let x = x_column.and_then(|c| table.column(c));
+        table
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| {
+                let x = match x {
+                    Some(x) => *row.get(x)?.as_ref()? as f64,
+                    None => i as f64,
+                };
+                Some([x, *row.get(y)?.as_ref()? as f64])
+            })
+            .collect()
+    }
+}
+
+/// Aligns the runs at the first time a column crosses a threshold
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct Trigger {
+    pub enabled: bool,
+    pub column: String,
+    pub threshold: f64,
+    pub edge: Edge,
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Trigger {
+            enabled: false,
+            column: String::new(),
+            threshold: 0.0,
+            edge: Edge::Rising,
+        }
+    }
+}
+
+/// Overlays one column of several runs with the same columns
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Overlay {
+    pub runs: Vec<Run>,
+    pub column: String,
+    /// empty to use the row index
+    pub x_column: String,
+    pub trigger: Trigger,
+    show_interface: bool,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Overlay {
+            runs: vec![],
+            column: String::new(),
+            x_column: String::new(),
+            trigger: Trigger::default(),
+            show_interface: true,
+        }
+    }
+}
+
+#[typetag::serde]
+impl TabStruct for Overlay {
+    fn interface(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Add files…").clicked() {
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(paths) = rfd::FileDialog::new().add_filter("csv", &["csv"]).pick_files() {
+                    for path in paths {
+                        self.runs.push(Run::new(path.display().to_string()));
+                    }
+                }
+            }
+            if ui.button("Reload all").clicked() {
+                self.runs.iter_mut().for_each(|run| run.load());
+            }
+        });
+        let header = self.header();
+        let column_combo = |ui: &mut Ui, label: &str, value: &mut String, index: bool| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                egui::ComboBox::from_id_source(ui.next_auto_id())
+                    .selected_text(if value.is_empty() && index {
+                        "index".to_string()
+                    } else {
+                        value.clone()
+                    })
+                    .show_ui(ui, |ui| {
+                        if index {
+                            ui.selectable_value(value, String::new(), "index");
+                        }
+                        for column in &header {
+                            ui.selectable_value(value, column.clone(), column);
+                        }
+                    });
+            });
+        };
+        column_combo(ui, "Column: ", &mut self.column, false);
+        column_combo(ui, "X axis: ", &mut self.x_column, true);
+        CollapsingHeader::new("Trigger")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.checkbox(&mut self.trigger.enabled, "Align runs at a trigger");
+                column_combo(ui, "Trigger column: ", &mut self.trigger.column, false);
+                ui.horizontal(|ui| {
+                    ui.add(
+                        DragValue::new(&mut self.trigger.threshold)
+                            .speed(0.1)
+                            .prefix("threshold: "),
+                    );
+                    egui::ComboBox::from_id_source(ui.next_auto_id())
+                        .selected_text(self.trigger.edge.to_string())
+                        .show_ui(ui, |ui| {
+                            for edge in [Edge::Rising, Edge::Falling, Edge::Both] {
+                                ui.selectable_value(&mut self.trigger.edge, edge, edge.to_string());
+                            }
+                        });
+                });
+            });
+        ui.separator();
+        let triggers = self.runs.iter().map(|run| self.trigger_x(run)).collect::<Vec<_>>();
+        let mut to_remove = None;
+        for (i, run) in self.runs.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.checkbox(&mut run.enabled, "");
+                let mut color = run.color.unwrap_or(get_color(i + 1));
+                if ui.color_edit_button_srgba(&mut color).changed() {
+                    run.color = Some(color);
+                }
+                ui.label(run.short_name()).on_hover_text(&run.file_name);
+            });
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut run.offset).speed(0.1).prefix("offset: "));
+                if self.trigger.enabled {
+                    match triggers[i] {
+                        Some(x) => ui.label(format!("trigger at {:.3}", x)),
+                        None => ui.colored_label(Color32::YELLOW, "no trigger"),
+                    };
+                }
+            });
+            if let Some(error) = &run.error {
+                ui.colored_label(Color32::RED, error);
+            }
+            if !header.is_empty() && run.table.as_ref().is_some_and(|t| t.header != header) {
+                ui.colored_label(Color32::YELLOW, "The columns differ from the first run");
+            }
+        }
+        if let Some(i) = to_remove {
+            self.runs.remove(i);
+        }
+    }
+
+    fn show_interface(&mut self) -> bool {
+        self.show_interface
+    }
+
+    fn title_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(self.title());
+            ui.checkbox(&mut self.show_interface, "Show Interface")
+        });
+    }
+
+    fn plot(&mut self, ui: &mut Ui) {
+        self.runs.iter_mut().for_each(|run| run.ensure_loaded());
+        let x_column = (!self.x_column.is_empty()).then_some(self.x_column.as_str());
+        Plot::new(ui.next_auto_id())
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                for (i, run) in self.runs.iter().enumerate() {
+                    if !run.enabled {
+                        continue;
+                    }
+                    let shift = run.offset - self.trigger_x(run).unwrap_or(0.0);
+                    let points = run
+                        .series(x_column, &self.column)
+                        .into_iter()
+                        .map(|[x, y]| [x + shift, y])
+                        .collect::<Vec<_>>();
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(points))
+                            .color(run.color.unwrap_or(get_color(i + 1)))
+                            .name(run.short_name()),
+                    );
+                }
+                if self.trigger.enabled {
+                    plot_ui.vline(VLine::new(0.0).color(Color32::GRAY).name("trigger"));
+                }
+            });
+    }
+
+    fn title(&self) -> String {
+        format!("Overlay: {}", self.column)
+    }
+}
+
+impl Overlay {
+    /// Columns of the first run that could be loaded
+    fn header(&self) -> Vec<String> {
+        self.runs
+            .iter()
+            .find_map(|run| run.table.as_ref())
+            .map(|table| table.header.clone())
+            .unwrap_or_default()
+    }
+
+    /// x value of the trigger of the run, None if the trigger is disabled or never fires
+    fn trigger_x(&self, run: &Run) -> Option<f64> {
+        if !self.trigger.enabled {
+            return None;
+        }
+        let x_column = (!self.x_column.is_empty()).then_some(self.x_column.as_str());
+        let series = run.series(x_column, &self.trigger.column);
+        let x = series.iter().map(|p| p[0]).collect::<Vec<_>>();
+        let y = series.iter().map(|p| p[1]).collect::<Vec<_>>();
+        first_crossing(&x, &y, self.trigger.threshold, self.trigger.edge)
+    }
+}
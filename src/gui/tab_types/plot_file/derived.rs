@@ -0,0 +1,85 @@
+use crate::gui::tab_types::plotter::compiler::Compiler;
+use crate::gui::tab_types::plotter::vm::VM;
+
+/// A column that is computed from the other columns of the file, e.g. `speed_l - speed_r`
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+#[serde(default)]
+pub struct DerivedColumn {
+    pub name: String,
+    pub expression: String,
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+impl DerivedColumn {
+    /// The name shown in the legend, falls back to the expression if no name was given
+    pub fn display_name(&self) -> String {
+        if self.name.trim().is_empty() {
+            self.expression.clone()
+        } else {
+            self.name.trim().to_string()
+        }
+    }
+
+    /// Evaluates the expression for every row.
+    /// `variables` are the variable names of the columns, in the same order as the values in a row
+    pub fn compute(&mut self, variables: &[String], rows: &[Vec<Option<f32>>]) -> Vec<Option<f32>> {
+        self.error = None;
+        let (instructions, identifiers) =
+            match Compiler::new().optimized_compile(self.expression.clone()) {
+                Ok(instructions) => instructions,
+                Err(e) => {
+                    self.error = Some(e);
+                    return vec![None; rows.len()];
+                }
+            };
+        // map every identifier of the expression to the index of its column
+        let mut indices = Vec::with_capacity(identifiers.len());
+        for identifier in &identifiers {
+            match variables.iter().position(|v| v == identifier) {
+                Some(i) => indices.push(i),
+                None => {
+                    self.error = Some(format!("Unknown column: {}", identifier));
+                    return vec![None; rows.len()];
+                }
+            }
+        }
+        let mut values = vec![0.0; identifiers.len()];
+        rows.iter()
+            .map(|row| {
+                for (value, index) in values.iter_mut().zip(&indices) {
+                    // rows with a missing value also have a missing derived value
+                    *value = (*row.get(*index)?)? as f64;
+                }
+                match VM::run((&instructions, &identifiers), &values) {
+                    Ok(v) => Some(v as f32),
+                    Err(e) => {
+                        if self.error.is_none() {
+                            self.error = Some(e);
+                        }
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Converts a column header into a name that can be used as a variable in an expression,
+/// e.g. "right color" becomes "right_color"
+pub fn variable_name(header: &str) -> String {
+    let mut name = String::new();
+    for c in header.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let mut name = name.trim_end_matches('_').to_string();
+    // identifiers can't start with a digit
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
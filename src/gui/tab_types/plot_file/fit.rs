@@ -0,0 +1,284 @@
+use crate::gui::plot_tools::layout::format_value;
+use crate::gui::tab_types::plotter::compiler::Compiler;
+use crate::gui::tab_types::plotter::parser::Operation;
+use crate::gui::tab_types::plotter::vm::VM;
+use crate::signal::fit::{levenberg_marquardt, FitResult};
+use egui::{Color32, DragValue, Ui};
+use egui_plot::{Legend, Line, Plot, PlotPoints, Points};
+use std::collections::HashMap;
+
+/// name of the independent variable in the model
+const X: &str = "x";
+const MAX_ITERATIONS: usize = 500;
+
+/// Models that are used often, linear and polynomial regression are just fits of these
+const PRESETS: [(&str, &str); 6] = [
+    ("Linear", "a*x+b"),
+    ("Quadratic", "a*x^2+b*x+c"),
+    ("Cubic", "a*x^3+b*x^2+c*x+d"),
+    ("Exponential decay", "a*exp(-b*x)+c"),
+    ("Sine", "a*sin(b*x+c)+d"),
+    ("Power", "a*x^b"),
+];
+
+/// A compiled model, every identifier except x is a free parameter
+struct Model {
+    instructions: Vec<Operation>,
+    identifiers: Vec<String>,
+    x_index: Option<usize>,
+    parameters: Vec<usize>,
+}
+
+impl Model {
+    fn compile(expression: &str) -> Result<Model, String> {
+        let (instructions, identifiers) = Compiler::new().optimized_compile(expression.to_string())?;
+        Ok(Model {
+            x_index: identifiers.iter().position(|i| i == X),
+            parameters: (0..identifiers.len())
+                .filter(|i| identifiers[*i] != X)
+                .collect(),
+            instructions,
+            identifiers,
+        })
+    }
+
+    fn parameter_names(&self) -> Vec<String> {
+        self.parameters
+            .iter()
+            .map(|i| self.identifiers[*i].clone())
+            .collect()
+    }
+
+    fn eval(&self, parameters: &[f64], x: f64) -> Result<f64, String> {
+        let mut values = vec![0.0; self.identifiers.len()];
+        if let Some(i) = self.x_index {
+            values[i] = x;
+        }
+        for (i, value) in self.parameters.iter().zip(parameters) {
+            values[*i] = *value;
+        }
+        VM::run((&self.instructions, &self.identifiers), &values)
+    }
+}
+
+struct FitOutput {
+    names: Vec<String>,
+    result: FitResult,
+    points: Vec<[f64; 2]>,
+    /// the model evaluated over the range of the points
+    curve: Vec<[f64; 2]>,
+}
+
+/// Fits a model written in the plotter language to two columns of the file
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CurveFit {
+    /// show the fit and the residuals below the plot
+    pub show: bool,
+    pub x: String,
+    pub y: String,
+    pub expression: String,
+    /// start values of the parameters, 1 if not set
+    pub initial: HashMap<String, f64>,
+    #[serde(skip)]
+    output: Option<FitOutput>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for CurveFit {
+    fn default() -> Self {
+        CurveFit {
+            show: false,
+            x: String::new(),
+            y: String::new(),
+            expression: PRESETS[0].1.to_string(),
+            initial: HashMap::new(),
+            output: None,
+            error: None,
+        }
+    }
+}
+
+impl CurveFit {
+    pub fn visible(&self) -> bool {
+        self.show && self.output.is_some()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, columns: &[String], rows: &[&[Option<f32>]]) {
+        let column_combo = |ui: &mut Ui, label: &str, value: &mut String| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                egui::ComboBox::from_id_source(ui.next_auto_id())
+                    .selected_text(value.clone())
+                    .show_ui(ui, |ui| {
+                        for column in columns {
+                            ui.selectable_value(value, column.clone(), column);
+                        }
+                    });
+            });
+        };
+        column_combo(ui, "X: ", &mut self.x);
+        column_combo(ui, "Y: ", &mut self.y);
+        ui.horizontal(|ui| {
+            ui.label("Model: ");
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text("Presets")
+                .show_ui(ui, |ui| {
+                    for (name, expression) in PRESETS {
+                        if ui.selectable_label(false, format!("{}: {}", name, expression)).clicked() {
+                            self.expression = expression.to_string();
+                        }
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("y = ");
+            ui.text_edit_singleline(&mut self.expression);
+        });
+        match Model::compile(&self.expression) {
+            Ok(model) => {
+                ui.label("Start values:");
+                for name in model.parameter_names() {
+                    ui.horizontal(|ui| {
+                        ui.label(&name);
+                        let value = self.initial.entry(name).or_insert(1.0);
+                        ui.add(DragValue::new(value).speed(0.01));
+                    });
+                }
+            }
+            Err(e) => {
+                ui.colored_label(Color32::RED, e);
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Fit").clicked() {
+                match self.fit(columns, rows) {
+                    Ok(output) => {
+                        self.output = Some(output);
+                        self.error = None;
+                    }
+                    Err(e) => {
+                        self.output = None;
+                        self.error = Some(e);
+                    }
+                }
+            }
+            ui.checkbox(&mut self.show, "Show fit and residuals");
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        if let Some(output) = &self.output {
+            let mut text = String::new();
+            for ((name, value), error) in output
+                .names
+                .iter()
+                .zip(&output.result.parameters)
+                .zip(&output.result.errors)
+            {
+                text += &format!("{} = {} ± {}\n", name, format_value(*value), format_value(*error));
+            }
+            text += &format!(
+                "R² = {}\nRSS = {}\n{} points, {} iterations",
+                format_value(output.result.r_squared),
+                format_value(output.result.rss),
+                output.points.len(),
+                output.result.iterations
+            );
+            ui.label(&text);
+            if ui.button("Use as start values").clicked() {
+                for (name, value) in output.names.iter().zip(&output.result.parameters) {
+                    self.initial.insert(name.clone(), *value);
+                }
+            }
+        }
+    }
+
+    fn fit(&self, columns: &[String], rows: &[&[Option<f32>]]) -> Result<FitOutput, String> {
+        let column = |name: &str| {
+            columns
+                .iter()
+                .position(|c| c == name)
+                .ok_or(format!("Unknown column: \"{}\"", name))
+        };
+        let (x, y) = (column(&self.x)?, column(&self.y)?);
+        let model = Model::compile(&self.expression)?;
+        let points = rows
+            .iter()
+            .filter_map(|row| Some([(*row.get(x)?)? as f64, (*row.get(y)?)? as f64]))
+            .collect::<Vec<_>>();
+        let names = model.parameter_names();
+        let initial = names
+            .iter()
+            .map(|name| self.initial.get(name).copied().unwrap_or(1.0))
+            .collect::<Vec<_>>();
+        let xs = points.iter().map(|p| p[0]).collect::<Vec<_>>();
+        let ys = points.iter().map(|p| p[1]).collect::<Vec<_>>();
+        let result = levenberg_marquardt(
+            |parameters, x| model.eval(parameters, x),
+            &xs,
+            &ys,
+            &initial,
+            MAX_ITERATIONS,
+        )?;
+        let min = xs.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let curve = (0..=500)
+            .filter_map(|i| {
+                let x = min + (max - min) * i as f64 / 500.0;
+                Some([x, model.eval(&result.parameters, x).ok()?])
+            })
+            .collect();
+        Ok(FitOutput {
+            names,
+            result,
+            points,
+            curve,
+        })
+    }
+
+    /// Draws the data with the fitted model and the residuals below it
+    pub fn show(&self, ui: &mut Ui, height: f32) {
+        let Some(output) = &self.output else {
+            return;
+        };
+        let link = ui.id().with("fit");
+        Plot::new(ui.next_auto_id())
+            .legend(Legend::default())
+            .height(height * 2.0 / 3.0)
+            .link_axis(link, true, false)
+            .link_cursor(link, true, false)
+            .show(ui, |plot_ui| {
+                plot_ui.points(
+                    Points::new(PlotPoints::from(output.points.clone()))
+                        .radius(1.5)
+                        .name(&self.y),
+                );
+                plot_ui.line(
+                    Line::new(PlotPoints::from(output.curve.clone()))
+                        .color(Color32::RED)
+                        .name(format!("fit: {}", self.expression)),
+                );
+            });
+        let residuals = output
+            .points
+            .iter()
+            .zip(&output.result.residuals)
+            .map(|(p, r)| [p[0], *r])
+            .collect::<Vec<_>>();
+        Plot::new(ui.next_auto_id())
+            .legend(Legend::default())
+            .height(height / 3.0)
+            .link_axis(link, true, false)
+            .link_cursor(link, true, false)
+            .show(ui, |plot_ui| {
+                plot_ui.points(
+                    Points::new(PlotPoints::from(residuals))
+                        .radius(1.5)
+                        .color(Color32::LIGHT_BLUE)
+                        .name("residuals"),
+                );
+            });
+    }
+}
@@ -0,0 +1,367 @@
+use crate::gui::tab_types::plot_file::table::{parse_csv, Table};
+use flate2::read::GzDecoder;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+
+/// Extension of the compact columnar format
+pub const COMPACT_EXTENSION: &str = "pcol";
+/// First bytes of a compact file, the last byte is the version
+const COMPACT_MAGIC: [u8; 8] = *b"PHXCOL\0\x01";
+
+/// The file formats PlotFile can open, chosen by the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Csv,
+    CsvGz,
+    CsvLz4,
+    /// one json object per line, the keys are the columns
+    NdJson,
+    /// the columns as bincode, see [`save_compact`]
+    Compact,
+}
+
+impl Display for DataFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataFormat::Csv => "csv",
+            DataFormat::CsvGz => "gzip compressed csv",
+            DataFormat::CsvLz4 => "lz4 compressed csv",
+            DataFormat::NdJson => "newline delimited json",
+            DataFormat::Compact => "compact columns",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl DataFormat {
+    pub fn from_path(path: &str) -> Option<DataFormat> {
+        let path = path.to_lowercase();
+        if path.ends_with(".csv.gz") {
+            Some(DataFormat::CsvGz)
+        } else if path.ends_with(".csv.lz4") {
+            Some(DataFormat::CsvLz4)
+        } else if path.ends_with(".csv") || path.ends_with(".txt") {
+            Some(DataFormat::Csv)
+        } else if path.ends_with(".ndjson") || path.ends_with(".jsonl") {
+            Some(DataFormat::NdJson)
+        } else if path.ends_with(&format!(".{}", COMPACT_EXTENSION)) {
+            Some(DataFormat::Compact)
+        } else {
+            None
+        }
+    }
+
+    /// Whether annotations can be written into the file as `#` comment lines
+    pub fn can_insert_comments(&self) -> bool {
+        *self == DataFormat::Csv
+    }
+}
+
+/// The file extensions of all formats, for file dialogs
+pub fn extensions() -> [&'static str; 7] {
+    [
+        "csv",
+        "txt",
+        "gz",
+        "lz4",
+        "ndjson",
+        "jsonl",
+        COMPACT_EXTENSION,
+    ]
+}
+
+/// Reads a table in any of the formats, files with an unknown extension are read as csv
+pub fn read_table(path: &str) -> Result<Table, String> {
+    let bytes = std::fs::read(path).map_err(|_| format!("Could not open file \"{}\"", path))?;
+    let text = |bytes: Vec<u8>| {
+        String::from_utf8(bytes).map_err(|_| format!("File \"{}\" is not valid utf-8", path))
+    };
+    let empty = || format!("File \"{}\" is empty", path);
+    match DataFormat::from_path(path).unwrap_or(DataFormat::Csv) {
+        DataFormat::Csv => parse_csv(&text(bytes)?).ok_or_else(empty),
+        DataFormat::CsvGz => {
+            let mut contents = String::new();
+            GzDecoder::new(bytes.as_slice())
+                .read_to_string(&mut contents)
+                .map_err(|e| format!("Could not decompress \"{}\": {}", path, e))?;
+            parse_csv(&contents).ok_or_else(empty)
+        }
+        DataFormat::CsvLz4 => {
+            let contents = decompress_lz4(&bytes)
+                .map_err(|e| format!("Could not decompress \"{}\": {}", path, e))?;
+            parse_csv(&text(contents)?).ok_or_else(empty)
+        }
+        DataFormat::NdJson => parse_ndjson(&text(bytes)?).ok_or_else(empty),
+        DataFormat::Compact => read_compact(&bytes).map_err(|e| format!("\"{}\": {}", path, e)),
+    }
+}
+
+/// Magic number of the lz4 frame format that the lz4 command line tool writes
+const LZ4_FRAME_MAGIC: u32 = 0x184D2204;
+
+/// Decompresses lz4 frames, or a single raw block like `lz4_compression::compress` writes it
+fn decompress_lz4(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    if bytes.len() < 4
+        || u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) != LZ4_FRAME_MAGIC
+    {
+        return lz4_compression::decompress::decompress(bytes).map_err(|e| format!("{:?}", e));
+    }
+    let truncated = || "The lz4 frame is truncated".to_string();
+    let mut output = vec![];
+    let mut i = 0;
+    // there can be several frames after each other
+    while i + 4 <= bytes.len() {
+        if u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+            != LZ4_FRAME_MAGIC
+        {
+            return Err("Unknown lz4 frame".to_string());
+        }
+        let flags = *bytes.get(i + 4).ok_or_else(truncated)?;
+        if flags >> 6 != 1 {
+            return Err("Unsupported lz4 frame version".to_string());
+        }
+        let block_checksum = flags & 0b1_0000 != 0;
+        let content_size = flags & 0b1000 != 0;
+        let content_checksum = flags & 0b100 != 0;
+        let dictionary = flags & 0b1 != 0;
+        // magic, flags, block descriptor, optional content size and dictionary id, header checksum
+        i += 6 + 8 * content_size as usize + 4 * dictionary as usize + 1;
+        loop {
+            let size = bytes.get(i..i + 4).ok_or_else(truncated)?;
+            let size = u32::from_le_bytes([size[0], size[1], size[2], size[3]]);
+            i += 4;
+            if size == 0 {
+                break;
+            }
+            let length = (size & 0x7FFF_FFFF) as usize;
+            let block = bytes.get(i..i + length).ok_or_else(truncated)?;
+            if size & 0x8000_0000 != 0 {
+                output.extend_from_slice(block);
+            } else {
+                // linked blocks can refer to the output of the blocks before
+                decode_lz4_block(block, &mut output)?;
+            }
+            i += length + 4 * block_checksum as usize;
+        }
+        i += 4 * content_checksum as usize;
+    }
+    Ok(output)
+}
+
+/// Decodes one lz4 block and appends it to `output`
+fn decode_lz4_block(block: &[u8], output: &mut Vec<u8>) -> Result<(), String> {
+    let invalid = || "Invalid lz4 block".to_string();
+    let mut i = 0;
+    let read_length = |i: &mut usize, mut length: usize| -> Result<usize, String> {
+        if length == 15 {
+            loop {
+                let byte = *block.get(*i).ok_or_else(invalid)?;
+                *i += 1;
+                length += byte as usize;
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Ok(length)
+    };
+    while i < block.len() {
+        let token = block[i];
+        i += 1;
+        let literals = read_length(&mut i, (token >> 4) as usize)?;
+        output.extend_from_slice(block.get(i..i + literals).ok_or_else(invalid)?);
+        i += literals;
+        // the last sequence only has literals
+        if i >= block.len() {
+            break;
+        }
+        let offset = block.get(i..i + 2).ok_or_else(invalid)?;
+        let offset = u16::from_le_bytes([offset[0], offset[1]]) as usize;
+        i += 2;
+        let length = read_length(&mut i, (token & 0xF) as usize)? + 4;
+        if offset == 0 || offset > output.len() {
+            return Err(invalid());
+        }
+        // the match can overlap the bytes it produces, so it's copied byte by byte
+        let start = output.len() - offset;
+        for j in 0..length {
+            output.push(output[start + j]);
+        }
+    }
+    Ok(())
+}
+
+/// Parses one json object per line, the columns are the keys in the order they first appear.
+/// Numbers and booleans are values, null and missing keys are empty, lines starting with `#` are comments.
+pub fn parse_ndjson(contents: &str) -> Option<Table> {
+    let mut table = Table::default();
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            table
+                .comments
+                .push((line_number, table.rows.len(), comment.trim().to_string()));
+            continue;
+        }
+        let object = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(serde_json::Value::Object(object)) => object,
+            Ok(_) => {
+                table.parse_error = Some(format!("Line {} is not a json object", line_number));
+                continue;
+            }
+            Err(e) => {
+                table.parse_error = Some(format!("Could not parse line {}: {}", line_number, e));
+                continue;
+            }
+        };
+        let mut row = vec![None; table.header.len()];
+        for (key, value) in object {
+            let column = match table.header.iter().position(|h| *h == key) {
+                Some(column) => column,
+                None => {
+                    table.header.push(key.clone());
+                    row.push(None);
+                    table.header.len() - 1
+                }
+            };
+            row[column] = match value {
+                serde_json::Value::Number(n) => n.as_f64().map(|n| n as f32),
+                serde_json::Value::Bool(b) => Some(b as u8 as f32),
+                serde_json::Value::Null => None,
+                serde_json::Value::String(s) => match s.trim().parse::<f32>() {
+                    Ok(v) => Some(v),
+                    Err(_) => {
+                        table.parse_error = Some(format!(
+                            "Could not parse value \"{}\" of \"{}\" (line {})",
+                            s, key, line_number
+                        ));
+                        None
+                    }
+                },
+                _ => {
+                    table.parse_error = Some(format!(
+                        "\"{}\" is not a number (line {})",
+                        key, line_number
+                    ));
+                    None
+                }
+            };
+        }
+        table.rows.push(row);
+    }
+    // rows before a new key appeared are shorter than the header
+    let columns = table.header.len();
+    table
+        .rows
+        .iter_mut()
+        .for_each(|row| row.resize(columns, None));
+    if table.header.is_empty() && table.comments.is_empty() {
+        None
+    } else {
+        Some(table)
+    }
+}
+
+/// The table stored column by column, missing values are NaN
+#[derive(serde::Deserialize, serde::Serialize)]
+struct CompactTable {
+    magic: [u8; 8],
+    header: Vec<String>,
+    rows: usize,
+    columns: Vec<Vec<f32>>,
+    comments: Vec<(usize, usize, String)>,
+}
+
+fn read_compact(bytes: &[u8]) -> Result<Table, String> {
+    // the magic is checked first, so other files aren't decoded into huge allocations
+    if !bytes.starts_with(&COMPACT_MAGIC) {
+        return Err("Not a compact data file or written by a different version".to_string());
+    }
+    let compact: CompactTable =
+        bincode::deserialize(bytes).map_err(|e| format!("Not a compact data file: {}", e))?;
+    // the sizes come from the file, a damaged one must not allocate the rows or give rows of the wrong length
+    if compact.columns.len() != compact.header.len() {
+        return Err(format!(
+            "Damaged compact data file: {} columns but {} column names",
+            compact.columns.len(),
+            compact.header.len()
+        ));
+    }
+    if compact.columns.is_empty() && compact.rows > 0 {
+        return Err(format!("Damaged compact data file: {} rows without columns", compact.rows));
+    }
+    if let Some((name, column)) = compact
+        .header
+        .iter()
+        .zip(&compact.columns)
+        .find(|(_, column)| column.len() != compact.rows)
+    {
+        return Err(format!(
+            "Damaged compact data file: the column \"{}\" has {} values instead of {}",
+            name,
+            column.len(),
+            compact.rows
+        ));
+    }
+    let mut rows = vec![Vec::with_capacity(compact.header.len()); compact.rows];
+    for column in &compact.columns {
+        for (row, value) in rows.iter_mut().zip(column) {
+            row.push(if value.is_nan() { None } else { Some(*value) });
+        }
+    }
+    Ok(Table {
+        header: compact.header,
+        rows,
+        comments: compact.comments,
+        parse_error: None,
+    })
+}
+
+/// Writes the table in the compact columnar format
+pub fn write_compact(table: &Table, path: &str) -> Result<(), String> {
+    let columns = (0..table.header.len())
+        .map(|c| {
+            table
+                .rows
+                .iter()
+                .map(|row| row.get(c).copied().flatten().unwrap_or(f32::NAN))
+                .collect()
+        })
+        .collect();
+    let compact = CompactTable {
+        magic: COMPACT_MAGIC,
+        header: table.header.clone(),
+        rows: table.rows.len(),
+        columns,
+        comments: table.comments.clone(),
+    };
+    let bytes = bincode::serialize(&compact).map_err(|e| e.to_string())?;
+    std::fs::write(path, bytes).map_err(|e| format!("Could not write \"{}\": {}", path, e))
+}
+
+/// Path of the compact file next to `path`, e.g. `run.csv.gz` -> `run.pcol`
+pub fn compact_path(path: &str) -> String {
+    let lower = path.to_lowercase();
+    let stem_length = [".csv.gz", ".csv.lz4", ".csv", ".txt", ".ndjson", ".jsonl"]
+        .iter()
+        .find(|e| lower.ends_with(*e))
+        .map(|e| path.len() - e.len())
+        .unwrap_or(path.len());
+    format!("{}.{}", &path[..stem_length], COMPACT_EXTENSION)
+}
+
+/// Reads the file and writes it in the compact format next to it, returns the path of the new file
+pub fn save_compact(path: &str) -> Result<String, String> {
+    if DataFormat::from_path(path) == Some(DataFormat::Compact) {
+        return Err(format!("\"{}\" already is a compact file", path));
+    }
+    let table = read_table(path)?;
+    let compact = compact_path(path);
+    write_compact(&table, &compact)?;
+    Ok(compact)
+}
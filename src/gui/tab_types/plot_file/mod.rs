@@ -0,0 +1,1098 @@
+pub mod derived;
+pub mod fit;
+pub mod formats;
+pub mod preset;
+pub mod row_filter;
+pub mod table;
+pub mod units;
+
+use crate::export::{Figure, Panel, Series, SeriesShape};
+use crate::gui::plot_tools::annotations::{
+    insert_comments, read_sidecar, sidecar_path, write_sidecar, AnnotationAction, Annotations,
+};
+use crate::gui::plot_tools::cursors::Cursors;
+use crate::gui::plot_tools::filters::{hash_points, FilterPipelines};
+use crate::gui::plot_tools::layout::{y_range, AxisMap, PlotLayout};
+use crate::gui::plot_tools::spectrum::SpectrumView;
+use crate::gui::plot_tools::trajectory::TrajectoryView;
+use crate::gui::plot_tools::style::{ChartStyles, SeriesStyle, MAX_CATEGORIES};
+use crate::gui::tab_types::all_colors::{all_colors_table, ALL_COLORS_FILE};
+use crate::gui::tab_types::plot_file::derived::{variable_name, DerivedColumn};
+use crate::gui::tab_types::plot_file::fit::CurveFit;
+use crate::gui::tab_types::plot_file::formats::{read_table, save_compact, DataFormat};
+use crate::gui::tab_types::plot_file::preset::{
+    preset_path, read_preset, write_preset, write_preset_to_folder, PresetRef, ViewPreset,
+};
+use crate::gui::tab_types::plot_file::row_filter::RowFilter;
+use crate::gui::tab_types::plot_file::units::Units;
+use crate::gui::tab_types::TabStruct;
+use eframe::epaint::Color32;
+use egui_plot::{AxisHints, Legend, Plot, PlotPoints, PlotUi, Points};
+use egui::{CollapsingHeader, ScrollArea, Ui};
+#[cfg(target_arch = "wasm32")]
+use egui_file::FileDialog;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Colors for the plot
+pub const INDEX_COLORS: [&str; 128] = [
+    "#ffffff", "#FFFF00", "#1CE6FF", "#FF34FF", "#FF4A46", "#008941", "#006FA6", "#A30059",
+    "#FFDBE5", "#7A4900", "#0000A6", "#63FFAC", "#B79762", "#004D43", "#8FB0FF", "#997D87",
+    "#5A0007", "#809693", "#FEFFE6", "#1B4400", "#4FC601", "#3B5DFF", "#4A3B53", "#FF2F80",
+    "#61615A", "#BA0900", "#6B7900", "#00C2A0", "#FFAA92", "#FF90C9", "#B903AA", "#D16100",
+    "#DDEFFF", "#000035", "#7B4F4B", "#A1C299", "#300018", "#0AA6D8", "#013349", "#00846F",
+    "#372101", "#FFB500", "#C2FFED", "#A079BF", "#CC0744", "#C0B9B2", "#C2FF99", "#001E09",
+    "#00489C", "#6F0062", "#0CBD66", "#EEC3FF", "#456D75", "#B77B68", "#7A87A1", "#788D66",
+    "#885578", "#FAD09F", "#FF8A9A", "#D157A0", "#BEC459", "#456648", "#0086ED", "#886F4C",
+    "#34362D", "#B4A8BD", "#00A6AA", "#452C2C", "#636375", "#A3C8C9", "#FF913F", "#938A81",
+    "#575329", "#00FECF", "#B05B6F", "#8CD0FF", "#3B9700", "#04F757", "#C8A1A1", "#1E6E00",
+    "#7900D7", "#A77500", "#6367A9", "#A05837", "#6B002C", "#772600", "#D790FF", "#9B9700",
+    "#549E79", "#FFF69F", "#201625", "#72418F", "#BC23FF", "#99ADC0", "#3A2465", "#922329",
+    "#5B4534", "#FDE8DC", "#404E55", "#0089A3", "#CB7E98", "#A4E804", "#324E72", "#6A3A4C",
+    "#83AB58", "#001C1E", "#D1F7CE", "#004B28", "#C8D0F6", "#A3A489", "#806C66", "#222800",
+    "#BF5650", "#E83000", "#66796D", "#DA007C", "#FF1A59", "#8ADBB4", "#1E0200", "#5B4E51",
+    "#C895C5", "#320033", "#FF6832", "#66E1D3", "#CFCDAC", "#D0AC94", "#7ED379", "#012C58",
+];
+
+type PlotData = Vec<(Vec<(f32, f32)>, Color32, String)>;
+/// name, points, color, style and unit of a drawn line,
+/// and the points before they were mapped to the left axis if the line is on the right axis
+type SubplotLine = (String, Vec<[f64; 2]>, Color32, SeriesStyle, String, Option<Vec<[f64; 2]>>);
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlotFile {
+    #[serde(skip)]
+    // list of lines
+    pub data: PlotData,
+    #[serde(skip)]
+    raw_data: Vec<Vec<Option<f32>>>,
+    #[serde(skip)]
+    header: Vec<String>,
+    /// header and rows of the file including the derived columns
+    #[serde(skip)]
+    columns: Vec<String>,
+    #[serde(skip)]
+    rows: Vec<Vec<Option<f32>>>,
+    /// columns computed from the other columns, these are recomputed on every reload
+    pub derived_columns: Vec<DerivedColumn>,
+    /// rows that are hidden from the plots, the fit and the statistics
+    pub row_filter: RowFilter,
+    /// filter pipelines for the series, keyed by the series name
+    pub filters: FilterPipelines,
+    pub spectrum: SpectrumView,
+    /// path of the robot from the values of its wheels
+    pub trajectory: TrajectoryView,
+    /// chart style, color and marker size of every series
+    pub styles: ChartStyles,
+    /// subplots and y axes of the series
+    pub layout: PlotLayout,
+    /// units of the columns and the units they are shown in
+    pub units: Units,
+    /// x range of the plot in the last frame
+    #[serde(skip)]
+    visible_x: Option<(f64, f64)>,
+    /// x value under the pointer while it is over the plot
+    #[serde(skip)]
+    hovered_x: Option<f64>,
+    pub load_file_name: String,
+    #[serde(skip)]
+    pub loading_error: Option<String>,
+    #[serde(skip)]
+    pub load_data_message: Option<String>,
+    /// use the first value for the x coordinate of the "other" plots
+    pub x_axis: i16,
+    /// List of scaling factors for each line, maybe some plot is always 10x as big as the other lines
+    pub scaling_factors: Vec<f64>,
+    #[serde(skip)]
+    pub comments: Vec<String>,
+    /// the comments with the index of the data row that follows them
+    #[serde(skip)]
+    comment_rows: Vec<(usize, String)>,
+    pub annotations: Annotations,
+    pub cursors: Cursors,
+    pub fit: CurveFit,
+    #[serde(skip)]
+    annotation_message: Option<Result<String, String>>,
+    #[serde(skip)]
+    compact_message: Option<Result<String, String>>,
+    /// the file whose preset was applied last, so reloading it doesn't undo the changes made since
+    preset_file: String,
+    #[serde(skip)]
+    preset_message: Option<Result<String, String>>,
+    show_interface: bool,
+    // #[serde(skip)]
+    // #[cfg(target_arch = "wasm32")]
+    // open_file_dialog: Option<FileDialog>,
+}
+
+impl Default for PlotFile {
+    fn default() -> Self {
+        PlotFile {
+            data: vec![],
+            raw_data: vec![],
+            header: vec![],
+            columns: vec![],
+            rows: vec![],
+            derived_columns: vec![],
+            row_filter: RowFilter::default(),
+            filters: FilterPipelines::default(),
+            spectrum: SpectrumView::default(),
+            trajectory: TrajectoryView::default(),
+            styles: ChartStyles::default(),
+            layout: PlotLayout::default(),
+            units: Units::default(),
+            visible_x: None,
+            hovered_x: None,
+            load_file_name: "data.csv".to_owned(),
+            loading_error: None,
+            load_data_message: None,
+            x_axis: -1,
+            scaling_factors: vec![],
+            comments: vec![],
+            comment_rows: vec![],
+            annotations: Annotations::default(),
+            cursors: Cursors::default(),
+            fit: CurveFit::default(),
+            annotation_message: None,
+            compact_message: None,
+            preset_file: String::new(),
+            preset_message: None,
+            show_interface: true,
+        }
+    }
+}
+
+#[typetag::serde]
+impl TabStruct for PlotFile {
+    fn interface(&mut self, ui: &mut Ui) {
+        // choose file to load
+        ui.horizontal(|ui| {
+            ui.label("File Name ");
+            // ui.text_edit_singleline(&mut self.load_file_name).on_hover_text("File Format: .csv");
+            if ui.button("Open file…").clicked() {
+                if cfg!(not(target_arch = "wasm32")) {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("data", &formats::extensions())
+                            .add_filter("all files", &["*"])
+                            .pick_file()
+                        {
+                            self.load_file_name = path.display().to_string();
+                        }
+                    }
+                } else {}
+                self.load_data();
+            }
+            if ui.button("Reload").clicked() {
+                self.load_data();
+            }
+            if ui
+                .button("Save as compact")
+                .on_hover_text("Writes the file as columns in a binary format that loads a lot faster")
+                .clicked()
+            {
+                self.load_data_message = None;
+                match save_compact(&self.load_file_name) {
+                    Ok(path) => self.compact_message = Some(Ok(format!("Saved as \"{}\"", path))),
+                    Err(e) => self.compact_message = Some(Err(e)),
+                }
+            }
+        });
+        match &self.compact_message {
+            Some(Ok(message)) => {
+                ui.colored_label(Color32::GREEN, message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(Color32::RED, error);
+            }
+            None => {}
+        }
+        ui.label(format!("File: {}", self.load_file_name));
+        // ui.checkbox(&mut self.x_axis, "Use first variable for the X axis");
+        // add menu to select the variable for the x axis default is index of the variables
+        ui.horizontal(|ui| {
+            ui.label("X axis: ");
+            egui::ComboBox::from_label("X axis")
+                .selected_text(
+                    if self.x_axis == -1 || self.x_axis as usize >= self.data.len() {
+                        "index".to_string()
+                    } else {
+                        self.data[self.x_axis as usize].2.clone()
+                    },
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.x_axis, -1, "index");
+                    for i in 0..self.scaling_factors.len() {
+                        if i >= self.data.len() {
+                            // idk why this is needed but it is
+                            break;
+                        }
+                        ui.selectable_value(
+                            &mut self.x_axis,
+                            i as i16,
+                            self.data[i].2.clone().to_string(),
+                        );
+                    }
+                });
+        });
+        // todo add option to scroll through the data
+        CollapsingHeader::new("Scaling factors")
+            .default_open(false)
+            .show(ui, |ui| {
+                for i in 0..self.scaling_factors.len() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.scaling_factors[i]).speed(0.01));
+                        ui.label(format!(
+                            "{}{}",
+                            self.units.label(&self.data[i].2),
+                            if self.x_axis == i as i16 {
+                                " (X axis)"
+                            } else {
+                                ""
+                            }
+                        ));
+                    });
+                }
+            });
+        CollapsingHeader::new("Units")
+            .default_open(false)
+            .show(ui, |ui| self.units.ui(ui, &self.columns));
+        CollapsingHeader::new("Chart style")
+            .default_open(false)
+            .show(ui, |ui| {
+                let series = self
+                    .data
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i as i16 != self.x_axis)
+                    .map(|(_, d)| (d.2.clone(), d.1))
+                    .collect::<Vec<_>>();
+                self.styles.ui(ui, &series, &self.columns);
+            });
+        CollapsingHeader::new("Axes and subplots")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self
+                    .data
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i as i16 != self.x_axis)
+                    .map(|(_, d)| d.2.clone())
+                    .collect::<Vec<_>>();
+                self.layout.ui(ui, &names);
+            });
+        CollapsingHeader::new("Derived columns")
+            .default_open(false)
+            .show(ui, |ui| self.derived_columns_ui(ui));
+        CollapsingHeader::new("Rows")
+            .default_open(false)
+            .show(ui, |ui| self.row_filter.ui(ui, self.rows.len()));
+        CollapsingHeader::new("Filters")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.data.iter().map(|d| d.2.clone()).collect::<Vec<_>>();
+                self.filters.ui(ui, &names);
+            });
+        CollapsingHeader::new("Spectrum")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.data.iter().map(|d| d.2.clone()).collect::<Vec<_>>();
+                self.spectrum.ui(ui, &names);
+            });
+        CollapsingHeader::new("Trajectory")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.data.iter().map(|d| d.2.clone()).collect::<Vec<_>>();
+                self.trajectory.ui(ui, &names);
+            });
+        CollapsingHeader::new("Cursors")
+            .default_open(false)
+            .show(ui, |ui| self.cursors.ui(ui));
+        CollapsingHeader::new("Curve fit")
+            .default_open(false)
+            .show(ui, |ui| {
+                let rows = self
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| self.row_filter.keep(*i))
+                    .map(|(_, row)| row.as_slice())
+                    .collect::<Vec<_>>();
+                self.fit.ui(ui, &self.columns, &rows);
+            });
+        CollapsingHeader::new("Preset")
+            .default_open(false)
+            .show(ui, |ui| self.preset_ui(ui));
+        CollapsingHeader::new("Annotations")
+            .default_open(false)
+            .show(ui, |ui| {
+                match self.annotations.ui(ui) {
+                    AnnotationAction::WriteToFile => {
+                        self.annotation_message = Some(self.write_annotations_to_file());
+                    }
+                    AnnotationAction::WriteToSidecar => {
+                        let path = sidecar_path(&self.load_file_name);
+                        self.annotation_message = Some(
+                            write_sidecar(&path, &self.annotations.user)
+                                .map(|_| format!("Saved the annotations to \"{}\"", path)),
+                        );
+                    }
+                    AnnotationAction::None => {}
+                }
+                match &self.annotation_message {
+                    Some(Ok(message)) => {
+                        ui.colored_label(Color32::GREEN, message);
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(Color32::RED, error);
+                    }
+                    None => {}
+                }
+            });
+        if let Some(error) = self.loading_error.clone() {
+            ui.colored_label(Color32::RED, error);
+            self.load_data_message = None;
+        }
+        if let Some(message) = self.load_data_message.clone() {
+            ui.colored_label(Color32::GREEN, message);
+        }
+        if !self.comments.is_empty() {
+            ui.separator();
+            CollapsingHeader::new("Comments")
+                .default_open(true)
+                .show(ui, |ui| {
+                    ScrollArea::both().show(ui, |ui| {
+                        for comment in self.comments.clone() {
+                            ui.label(comment);
+                        }
+                    });
+                });
+        }
+    }
+
+    fn show_interface(&mut self) -> bool {
+        self.show_interface
+    }
+
+    fn title_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("Plot File: {}", self.load_file_name));
+            ui.checkbox(&mut self.show_interface, "Show Interface")
+        });
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "Plot File: {}",
+            self.load_file_name
+                .replace('\\', "/")
+                .split('/')
+                .last()
+                .unwrap_or("")
+        )
+    }
+
+    fn plot(&mut self, ui: &mut Ui) {
+        // the built in data isn't saved with the tab, so it has to be loaded again after a restart
+        if self.load_file_name == ALL_COLORS_FILE && self.columns.is_empty() {
+            self.load_data();
+        }
+        self.update_data();
+        // the spectrum, the trajectory and the fit get the same height as the main plot
+        let panels = 1
+            + self.spectrum.enabled as usize
+            + self.trajectory.enabled as usize
+            + self.fit.visible() as usize;
+        let height = ui.available_height() / panels as f32;
+        let allow_drag = self.cursors.allow_plot_drag() && self.row_filter.allow_plot_drag();
+        self.cursors.clear();
+        if self.styles.xy.enabled {
+            let x_unit = self.units.unit(&self.styles.xy.x).unwrap_or("").to_string();
+            self.cursors.set_x_unit(&x_unit);
+            Plot::new("my_plot")
+                .legend(Legend::default())
+                .x_axis_label(self.units.label(&self.styles.xy.x))
+                .y_axis_label(self.units.label(&self.styles.xy.y))
+                .height(height)
+                .allow_drag(allow_drag)
+                .show(ui, |plot_ui| {
+                    self.plot_xy(plot_ui);
+                    self.cursors.draw(plot_ui);
+                    let comments = self.comment_markers();
+                    self.annotations.draw(plot_ui, &comments, true);
+                    let bounds = plot_ui.plot_bounds();
+                    self.visible_x = Some((bounds.min()[0], bounds.max()[0]));
+                });
+        } else {
+            let subplots = self.layout.subplots();
+            let spacing = ui.spacing().item_spacing.y;
+            let subplot_height = (height - spacing * (subplots - 1) as f32) / subplots as f32;
+            // the subplots share the x axis and the cursor
+            let link = ui.id().with("subplots");
+            self.hovered_x = None;
+            let x_unit = self.x_column().and_then(|c| self.units.unit(c)).unwrap_or("").to_string();
+            self.cursors.set_x_unit(&x_unit);
+            for subplot in 0..subplots {
+                let (lines, map) = self.subplot_lines(subplot);
+                let (left_label, right_label) = self.axis_labels(subplot);
+                let mut plot = Plot::new(("my_plot", subplot))
+                    .legend(Legend::default())
+                    .height(subplot_height)
+                    .link_axis(link, true, false)
+                    .link_cursor(link, true, false)
+                    .allow_drag(allow_drag);
+                // the subplots share the x axis, so only the lowest one has the label
+                if subplot + 1 == subplots {
+                    plot = plot.x_axis_label(self.x_label());
+                }
+                if let Some(map) = map {
+                    let right_label = if right_label.is_empty() {
+                        "right axis".to_string()
+                    } else {
+                        right_label
+                    };
+                    plot = plot.custom_y_axes(vec![
+                        AxisHints::new_y().label(left_label),
+                        map.axis_hints(&right_label),
+                    ]);
+                } else {
+                    plot = plot.y_axis_label(left_label);
+                }
+                plot.show(ui, |plot_ui| {
+                    for (name, points, color, style, unit, unmapped) in lines {
+                        // the cursors show the values of the series, not the ones on the left axis
+                        let measured = unmapped.as_ref().unwrap_or(&points);
+                        self.cursors.measure_with_unit(&name, &unit, measured);
+                        style.draw(plot_ui, &name, points, color);
+                    }
+                    self.cursors.draw(plot_ui);
+                    self.row_filter.draw(plot_ui);
+                    if plot_ui.response().hovered() {
+                        self.hovered_x = plot_ui.pointer_coordinate().map(|p| p.x);
+                    }
+                    let comments = self.comment_markers();
+                    self.annotations.draw(plot_ui, &comments, subplot == 0);
+                    let bounds = plot_ui.plot_bounds();
+                    self.visible_x = Some((bounds.min()[0], bounds.max()[0]));
+                });
+            }
+        }
+        if self.spectrum.enabled {
+            let points = match self.data.iter().position(|d| d.2 == self.spectrum.column) {
+                Some(i) => self.scaled_points(i),
+                None => vec![],
+            };
+            ui.allocate_ui(egui::vec2(ui.available_width(), height), |ui| {
+                self.spectrum.show(ui, &points, self.visible_x);
+            });
+        }
+        if self.trajectory.enabled {
+            let points = |column: &str| match self.data.iter().position(|d| d.2 == column) {
+                Some(i) => self.scaled_points(i),
+                None => vec![],
+            };
+            let (right, left) = (points(&self.trajectory.right), points(&self.trajectory.left));
+            // the rows are read again every frame, so a change of the file or the scaling is noticed by the values
+            self.trajectory.set_input([hash_points(&right), hash_points(&left)]);
+            // the marker follows the first cursor, the pointer or the end of the visible range
+            let cursor = self
+                .cursors
+                .first()
+                .or(self.hovered_x)
+                .or(self.visible_x.map(|(_, max)| max));
+            ui.allocate_ui(egui::vec2(ui.available_width(), height), |ui| {
+                self.trajectory.show(ui, &right, &left, |p| p, cursor);
+            });
+        }
+        if self.fit.visible() {
+            self.fit.show(ui, height);
+        }
+    }
+
+    fn get_file_path(&self) -> Option<String> {
+        Some(self.load_file_name.clone())
+    }
+
+    fn figure(&mut self) -> Option<Figure> {
+        self.update_data();
+        let markers = self.annotations.markers(&self.comment_markers());
+        let mut figure = Figure {
+            title: self.load_file_name.clone(),
+            x_label: self.x_label(),
+            x_range: self.visible_x,
+            panels: vec![],
+        };
+        if self.styles.xy.enabled {
+            figure.x_label = self.units.label(&self.styles.xy.x);
+            let radius = self.styles.xy.marker_size;
+            let series = self
+                .xy_groups()
+                .into_iter()
+                .map(|(name, points, color)| Series {
+                    name,
+                    color,
+                    shape: SeriesShape::Points(points, radius),
+                })
+                .collect();
+            figure.panels.push(Panel {
+                series,
+                markers,
+                right_axis: None,
+            });
+            return Some(figure);
+        }
+        for subplot in 0..self.layout.subplots() {
+            let (lines, right_axis) = self.subplot_lines(subplot);
+            let series = lines
+                .into_iter()
+                .map(|(name, points, color, style, _, _)| style.export_series(&name, points, color))
+                .collect();
+            // like on the screen only the first subplot has the labels
+            let markers = markers
+                .iter()
+                .cloned()
+                .map(|mut m| {
+                    if subplot > 0 {
+                        m.text.clear();
+                    }
+                    m
+                })
+                .collect();
+            figure.panels.push(Panel {
+                series,
+                markers,
+                right_axis,
+            });
+        }
+        Some(figure)
+    }
+}
+
+impl PlotFile {
+    pub fn load_data(&mut self) {
+        self.loading_error = None;
+        self.load_data_message = None;
+        self.comments = Vec::new();
+        self.comment_rows = Vec::new();
+        if self.load_file_name == ALL_COLORS_FILE {
+            let (mut header, rows) = all_colors_table();
+            self.units.parse(&mut header, &mut vec![]);
+            self.header = header;
+            self.raw_data = rows;
+            self.compute_derived_columns();
+            self.update_data();
+            return;
+        }
+        let table = match read_table(&self.load_file_name) {
+            Ok(table) => table,
+            Err(e) => {
+                self.loading_error = Some(e);
+                return;
+            }
+        };
+        self.loading_error = table.parse_error;
+        let (mut header, mut comments) = (table.header, table.comments);
+        // units in the header and the unit and meta directives aren't part of the names and comments
+        self.units.parse(&mut header, &mut comments);
+        for (line, row, text) in comments {
+            self.comments.push(format!("{}: {}", line, text));
+            self.comment_rows.push((row, text));
+        }
+        self.header = header;
+        self.raw_data = table.rows;
+        self.compute_derived_columns();
+        let preset = preset_path(&self.load_file_name);
+        if self.preset_file != self.load_file_name && Path::new(&preset).exists() {
+            self.preset_message = Some(read_preset(&preset).map(|p| {
+                self.apply_preset(p);
+                format!("Applied the preset \"{}\"", preset)
+            }));
+        }
+        self.update_data();
+        let sidecar = sidecar_path(&self.load_file_name);
+        if Path::new(&sidecar).exists() {
+            match read_sidecar(&sidecar) {
+                Ok(annotations) => self.annotations.user = annotations,
+                Err(e) => self.annotation_message = Some(Err(e)),
+            }
+        }
+    }
+
+    /// The view configuration of the tab, see [`ViewPreset`]
+    pub fn preset(&self) -> PresetRef<'_> {
+        let x_axis = if self.x_axis >= 0 {
+            self.columns.get(self.x_axis as usize).map(|c| c.as_str()).unwrap_or("")
+        } else {
+            ""
+        };
+        PresetRef {
+            columns: &self.columns,
+            x_axis,
+            scaling_factors: &self.scaling_factors,
+            derived_columns: &self.derived_columns,
+            row_filter: &self.row_filter,
+            filters: &self.filters,
+            spectrum: &self.spectrum,
+            trajectory: &self.trajectory,
+            styles: &self.styles,
+            layout: &self.layout,
+            fit: &self.fit,
+            units: &self.units,
+        }
+    }
+
+    /// Replaces the view configuration with the preset, the columns of the file have to be loaded
+    pub fn apply_preset(&mut self, mut preset: ViewPreset) {
+        self.derived_columns = std::mem::take(&mut preset.derived_columns);
+        self.compute_derived_columns();
+        self.scaling_factors = self.columns.iter().map(|c| preset.scaling_factor(c)).collect();
+        self.x_axis = self
+            .columns
+            .iter()
+            .position(|c| *c == preset.x_axis)
+            .map(|i| i as i16)
+            .unwrap_or(-1);
+        self.row_filter = preset.row_filter;
+        self.filters = preset.filters;
+        self.spectrum = preset.spectrum;
+        self.trajectory = preset.trajectory;
+        self.styles = preset.styles;
+        self.layout = preset.layout;
+        self.fit = preset.fit;
+        self.units.display = preset.units.display;
+        self.preset_file = self.load_file_name.clone();
+    }
+
+    fn preset_ui(&mut self, ui: &mut Ui) {
+        ui.label("The view settings are saved next to the file and applied when it is opened again.");
+        let is_file = self.load_file_name != ALL_COLORS_FILE;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(is_file, egui::Button::new("Save preset")).clicked() {
+                let path = preset_path(&self.load_file_name);
+                self.preset_message = Some(
+                    write_preset(&path, &self.preset()).map(|_| format!("Saved the preset to \"{}\"", path)),
+                );
+                self.preset_file = self.load_file_name.clone();
+            }
+            if ui.add_enabled(is_file, egui::Button::new("Load preset")).clicked() {
+                let path = preset_path(&self.load_file_name);
+                self.preset_message = Some(read_preset(&path).map(|preset| {
+                    self.apply_preset(preset);
+                    format!("Loaded the preset from \"{}\"", path)
+                }));
+            }
+            if ui
+                .add_enabled(is_file, egui::Button::new("Apply preset to folder"))
+                .on_hover_text("Saves the preset for every data file in the folder of this file, existing presets are replaced")
+                .clicked()
+            {
+                self.preset_message = Some(
+                    write_preset_to_folder(&self.load_file_name, &self.preset())
+                        .map(|(count, skipped)| match skipped {
+                            0 => format!("Saved the preset for {} files", count),
+                            _ => format!(
+                                "Saved the preset for {} files, skipped {} files with other columns",
+                                count, skipped
+                            ),
+                        }),
+                );
+                self.preset_file = self.load_file_name.clone();
+            }
+        });
+        match &self.preset_message {
+            Some(Ok(message)) => {
+                ui.colored_label(Color32::GREEN, message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(Color32::RED, error);
+            }
+            None => {}
+        }
+    }
+
+    /// Scaling factor of the column times the conversion to the unit it is shown in
+    fn scale(&self, column: usize) -> f64 {
+        let unit = self.columns.get(column).map(|c| self.units.factor(c)).unwrap_or(1.0);
+        self.scaling_factors.get(column).copied().unwrap_or(1.0) * unit
+    }
+
+    /// x values are scaled with the first scaling factor and converted to the unit of the x column
+    fn x_scale(&self) -> f64 {
+        let unit = match self.x_column() {
+            Some(column) => self.units.factor(column),
+            None => 1.0,
+        };
+        self.scaling_factors.first().copied().unwrap_or(1.0) * unit
+    }
+
+    /// Name of the x column, None if the index is used
+    fn x_column(&self) -> Option<&str> {
+        if self.x_axis < 0 {
+            return None;
+        }
+        self.columns.get(self.x_axis as usize).map(|c| c.as_str())
+    }
+
+    /// Label of the x axis with the unit
+    fn x_label(&self) -> String {
+        match self.x_column() {
+            Some(column) => self.units.label(column),
+            None => "index".to_string(),
+        }
+    }
+
+    /// x value of the row with the index `row` in the plot
+    fn row_x(&self, row: usize) -> Option<f64> {
+        if self.x_axis == -1 {
+            Some(row as f64 * self.x_scale())
+        } else {
+            self.rows
+                .get(row)?
+                .get(self.x_axis as usize)
+                .copied()
+                .flatten()
+                .map(|x| x as f64 * self.x_scale())
+        }
+    }
+
+    /// The rows that pass the row filter
+    fn filtered_rows(&self) -> Vec<&[Option<f32>]> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.row_filter.keep(*i))
+            .map(|(_, row)| row.as_slice())
+            .collect()
+    }
+
+    /// Evaluates the row filter again if the filter, the x axis or the rows changed
+    fn update_row_mask(&mut self) {
+        let x_scale = self.x_scale();
+        if !self.row_filter.needs_update(self.x_axis, x_scale, self.rows.len()) {
+            return;
+        }
+        let variables = self
+            .columns
+            .iter()
+            .map(|c| variable_name(c))
+            .collect::<Vec<_>>();
+        let x = (0..self.rows.len()).map(|r| self.row_x(r)).collect::<Vec<_>>();
+        self.row_filter
+            .update(&variables, &self.rows, &x, self.x_axis, x_scale);
+    }
+
+    /// The comments of the file at the x value of the next row that has one
+    fn comment_markers(&self) -> Vec<(f64, String)> {
+        self.comment_rows
+            .iter()
+            .filter_map(|(row, text)| {
+                let x = (*row..self.rows.len()).find_map(|r| self.row_x(r))?;
+                Some((x, text.clone()))
+            })
+            .collect()
+    }
+
+    /// Inserts the user annotations as comments into the file and reloads it
+    fn write_annotations_to_file(&mut self) -> Result<String, String> {
+        if self.load_file_name == ALL_COLORS_FILE {
+            return Err("The built in data can't be written to".to_string());
+        }
+        if DataFormat::from_path(&self.load_file_name).is_some_and(|f| !f.can_insert_comments()) {
+            return Err("Annotations can only be written into csv files, save them to the sidecar file".to_string());
+        }
+        let contents = std::fs::read_to_string(&self.load_file_name)
+            .map_err(|e| format!("Could not read \"{}\": {}", self.load_file_name, e))?;
+        let row_x = (0..self.rows.len()).map(|r| self.row_x(r)).collect::<Vec<_>>();
+        let contents = insert_comments(&contents, &row_x, &self.annotations.user);
+        std::fs::write(&self.load_file_name, contents)
+            .map_err(|e| format!("Could not write \"{}\": {}", self.load_file_name, e))?;
+        let count = self.annotations.user.len();
+        self.annotations.user.clear();
+        // the annotations are in the file now, the sidecar would add them a second time
+        let sidecar = sidecar_path(&self.load_file_name);
+        if Path::new(&sidecar).exists() {
+            std::fs::remove_file(&sidecar)
+                .map_err(|e| format!("Could not remove \"{}\": {}", sidecar, e))?;
+        }
+        self.load_data();
+        Ok(format!("Wrote {} annotations to \"{}\"", count, self.load_file_name))
+    }
+
+    /// The points of the series with the index `i` with the scaling factors applied
+    fn scaled_points(&self, i: usize) -> Vec<[f64; 2]> {
+        let (x_scale, y_scale) = (self.x_scale(), self.scale(i));
+        self.data[i]
+            .0
+            .iter()
+            .map(|(x, y)| [(*x as f64) * x_scale, *y as f64 * y_scale])
+            .collect()
+    }
+
+    /// The lines of a subplot as they are drawn: name, points, color and style.
+    /// Series on the right axis are mapped to the left one with the returned map.
+    fn subplot_lines(&mut self, subplot: usize) -> (Vec<SubplotLine>, Option<AxisMap>) {
+        let series = (0..self.data.len())
+            .filter(|i| *i as i16 != self.x_axis)
+            .filter(|i| !self.styles.get(&self.data[*i].2).hidden)
+            .filter(|i| self.layout.placement(&self.data[*i].2).subplot == subplot)
+            .map(|i| (i, self.layout.placement(&self.data[i].2).right_axis, self.scaled_points(i)))
+            .collect::<Vec<_>>();
+        let left = y_range(series.iter().filter(|s| !s.1).flat_map(|s| &s.2));
+        let right = y_range(series.iter().filter(|s| s.1).flat_map(|s| &s.2));
+        let map = AxisMap::new(left, right);
+        let mut lines = vec![];
+        for (i, right_axis, points) in series {
+            let (default_color, series_name) = (self.data[i].1, self.data[i].2.clone());
+            let style = self.styles.get(&series_name);
+            let unit = self.units.unit(&series_name).unwrap_or("").to_string();
+            for (name, mut points, highlighted) in self.filters.lines(&series_name, points) {
+                let color = if highlighted {
+                    style.color(default_color)
+                } else {
+                    style.color(default_color).gamma_multiply(0.4)
+                };
+                let (name, unmapped) = if right_axis {
+                    let unmapped = points.clone();
+                    points.iter_mut().for_each(|p| p[1] = map.to_left(p[1]));
+                    (format!("{} (right)", name), Some(unmapped))
+                } else {
+                    (name, None)
+                };
+                lines.push((name, points, color, style.clone(), unit.clone(), unmapped));
+            }
+        }
+        (lines, right.map(|_| map))
+    }
+
+    /// Units of the series on the left and on the right axis of the subplot, for the axis labels
+    fn axis_labels(&self, subplot: usize) -> (String, String) {
+        let mut columns = [vec![], vec![]];
+        for (i, (_, _, name)) in self.data.iter().enumerate() {
+            let placement = self.layout.placement(name);
+            if i as i16 != self.x_axis && !self.styles.get(name).hidden && placement.subplot == subplot {
+                columns[placement.right_axis as usize].push(name.as_str());
+            }
+        }
+        let [left, right] = columns.map(|columns| self.units.axis_label(&columns));
+        (left, right)
+    }
+
+    /// Plots the column `styles.xy.y` against `styles.xy.x`, with one color per value of the group column
+    fn plot_xy(&mut self, plot_ui: &mut PlotUi) {
+        let unit = self.units.unit(&self.styles.xy.y).unwrap_or("").to_string();
+        for (name, points, color) in self.xy_groups() {
+            self.cursors.measure_with_unit(&name, &unit, &points);
+            plot_ui.points(
+                Points::new(PlotPoints::from(points))
+                    .color(color)
+                    .radius(self.styles.xy.marker_size)
+                    .name(name),
+            );
+        }
+    }
+
+    /// The points of the xy plot grouped by the value of the group column, with their name and color
+    fn xy_groups(&self) -> Vec<(String, Vec<[f64; 2]>, Color32)> {
+        let xy = &self.styles.xy;
+        let column = |name: &str| self.columns.iter().position(|c| c == name);
+        let (Some(x), Some(y)) = (column(&xy.x), column(&xy.y)) else {
+            return vec![];
+        };
+        let group = column(&xy.group);
+        let (x_scale, y_scale) = (self.scale(x), self.scale(y));
+        let mut groups: Vec<(f32, Vec<[f64; 2]>)> = vec![];
+        // index of the group of a key, NaN != NaN, so the bits are compared to put all rows without a group together
+        let mut indices = HashMap::new();
+        for row in self.filtered_rows() {
+            let (Some(x_value), Some(y_value)) = (row[x], row[y]) else {
+                continue;
+            };
+            let point = [
+                x_value as f64 * x_scale,
+                y_value as f64 * y_scale,
+            ];
+            let key = group.and_then(|g| row[g]).unwrap_or(f32::NAN);
+            let i = *indices.entry(key.to_bits()).or_insert_with(|| {
+                groups.push((key, vec![]));
+                groups.len() - 1
+            });
+            groups[i].1.push(point);
+        }
+        if groups.len() > MAX_CATEGORIES {
+            // too many different values to be groups, draw all points in one color
+            let name = format!(
+                "{} / {} (\"{}\" has more than {} values)",
+                xy.y, xy.x, xy.group, MAX_CATEGORIES
+            );
+            let points = groups.into_iter().flat_map(|g| g.1).collect();
+            let color = self.styles.get(&name).color(get_color(1));
+            return vec![(name, points, color)];
+        }
+        groups.sort_by(|a, b| a.0.total_cmp(&b.0));
+        groups
+            .into_iter()
+            .enumerate()
+            .map(|(i, (key, points))| {
+                let name = if key.is_nan() {
+                    format!("{} / {}", xy.y, xy.x)
+                } else {
+                    xy.group_name(key)
+                };
+                let color = self.styles.get(&name).color(get_color(i + 1));
+                (name, points, color)
+            })
+            .collect()
+    }
+
+    pub fn update_data(&mut self) {
+        if self.loading_error.is_none() {
+            self.data.clear();
+            // self.scaling_factors.clear();
+            // we assume that the first column is the x axisx
+            // let start = if self.x_axis != -1 { 1 } else { 0 };
+            let header = self.columns.clone();
+            let data = self.rows.clone();
+            // the x axis could point to a derived column that doesn't exist anymore
+            if self.x_axis as usize >= header.len() {
+                self.x_axis = -1;
+            }
+            for i in 0..header.len() {
+                // if the self.scaling_factors is empty, we fill it with 1.0
+                if self.scaling_factors.len() <= i {
+                    self.scaling_factors.push(1.0);
+                }
+            }
+            self.update_row_mask();
+            for i in 0..header.len() {
+                // if i == self.x_axis as usize {
+                //     continue;
+                // }
+                let mut column = Vec::new();
+                let mut j = 0;
+                for (r, row) in data.iter().enumerate() {
+                    if row.len() <= i {
+                        continue;
+                    }
+                    // hidden rows keep their index, so the x axis doesn't shift when filtering
+                    if !self.row_filter.keep(r) {
+                        if self.x_axis == -1 {
+                            j += 1;
+                        }
+                        continue;
+                    }
+                    if self.x_axis != -1 {
+                        if row[self.x_axis as usize].is_some() && row[i].is_some() {
+                            column.push((row[self.x_axis as usize].unwrap(), row[i].unwrap()));
+                        }
+                    } else {
+                        if row[i].is_some() {
+                            column.push((j as f32, row[i].unwrap()));
+                        }
+                        j += 1;
+                    }
+                }
+                let (r, g, b) = convert_hex_to_rgb(INDEX_COLORS[i % 128]);
+                self.data
+                    .push((column, Color32::from_rgb(r, g, b), header[i].clone()));
+            }
+            while self.scaling_factors.len() > header.len() {
+                self.scaling_factors.pop();
+            }
+            self.load_data_message = Some(if self.row_filter.is_active() {
+                format!(
+                    "Loaded {} rows ({} shown) with format {}",
+                    data.len(),
+                    self.row_filter.kept(),
+                    header.join(", ")
+                )
+            } else {
+                format!("Loaded {} rows with format {}", data.len(), header.join(", "))
+            });
+        }
+    }
+
+    /// Recomputes the derived columns and appends them to the rows of the file.
+    /// Derived columns can use the columns of the file and all derived columns defined before them.
+    pub fn compute_derived_columns(&mut self) {
+        let mut columns = self.header.clone();
+        let mut rows = self
+            .raw_data
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                row.resize(self.header.len(), None);
+                row
+            })
+            .collect::<Vec<_>>();
+        for derived in &mut self.derived_columns {
+            let variables = columns
+                .iter()
+                .map(|c| variable_name(c))
+                .collect::<Vec<_>>();
+            let values = derived.compute(&variables, &rows);
+            for (row, value) in rows.iter_mut().zip(values) {
+                row.push(value);
+            }
+            columns.push(derived.display_name());
+        }
+        self.columns = columns;
+        self.rows = rows;
+        self.row_filter.invalidate();
+    }
+
+    fn derived_columns_ui(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Variables")
+            .default_open(false)
+            .show(ui, |ui| {
+                for column in &self.columns {
+                    ui.label(format!("{} = {}", variable_name(column), column));
+                }
+            });
+        let mut to_remove = None;
+        for (i, derived) in self.derived_columns.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.add(egui::TextEdit::singleline(&mut derived.name).hint_text("name").desired_width(80.0));
+                ui.label("=");
+                ui.add(egui::TextEdit::singleline(&mut derived.expression).hint_text("speed_l - speed_r"));
+            });
+            if let Some(error) = &derived.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        }
+        if let Some(i) = to_remove {
+            self.derived_columns.remove(i);
+        }
+        ui.horizontal(|ui| {
+            if ui.button("+").clicked() {
+                self.derived_columns.push(DerivedColumn::default());
+            }
+            if ui.button("Apply").clicked() {
+                self.compute_derived_columns();
+                self.update_data();
+            }
+        });
+    }
+}
+
+/// Converts strings like #00ff00 to rgb values
+pub fn convert_hex_to_rgb(hex: &str) -> (u8, u8, u8) {
+    let mut hex = hex.to_string();
+    if hex.starts_with('#') {
+        hex.remove(0);
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).unwrap();
+    let g = u8::from_str_radix(&hex[2..4], 16).unwrap();
+    let b = u8::from_str_radix(&hex[4..6], 16).unwrap();
+    (r, g, b)
+}
+
+/// Returns a color from the INDEX_COLORS array, which should be different for each index
+pub fn get_color(index: usize) -> Color32 {
+    let (r, g, b) = convert_hex_to_rgb(INDEX_COLORS[index % 128]);
+    Color32::from_rgb(r, g, b)
+}
@@ -0,0 +1,125 @@
+use crate::gui::plot_tools::filters::FilterPipelines;
+use crate::gui::plot_tools::layout::PlotLayout;
+use crate::gui::plot_tools::spectrum::SpectrumView;
+use crate::gui::plot_tools::style::ChartStyles;
+use crate::gui::plot_tools::trajectory::TrajectoryView;
+use crate::gui::tab_types::plot_file::derived::DerivedColumn;
+use crate::gui::tab_types::plot_file::fit::CurveFit;
+use crate::gui::tab_types::plot_file::formats::{read_table, DataFormat};
+use crate::gui::tab_types::plot_file::row_filter::RowFilter;
+use crate::gui::tab_types::plot_file::units::{split_unit, Units};
+use crate::gui::plot_tools::annotations::sidecar_path;
+use std::fs;
+use std::path::Path;
+
+/// Extension of the preset file that is saved next to a data file
+pub const PRESET_EXTENSION: &str = "phoenix.ron";
+
+/// The view configuration of a PlotFile, saved next to the data file so it survives closing the tab.
+/// Scaling factors and the x axis are stored by column name, so a preset also fits files
+/// with the same columns in a different order.
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+pub struct ViewPreset {
+    /// the columns of the file the preset was saved for, including the derived ones
+    pub columns: Vec<String>,
+    /// column used for the x axis, empty for the index
+    pub x_axis: String,
+    /// scaling factor of every column in `columns`
+    pub scaling_factors: Vec<f64>,
+    pub derived_columns: Vec<DerivedColumn>,
+    pub row_filter: RowFilter,
+    pub filters: FilterPipelines,
+    pub spectrum: SpectrumView,
+    pub trajectory: TrajectoryView,
+    pub styles: ChartStyles,
+    pub layout: PlotLayout,
+    pub fit: CurveFit,
+    /// only the units the columns are shown in
+    pub units: Units,
+}
+
+impl ViewPreset {
+    /// Scaling factor of the column `name`, 1 if the preset doesn't know the column
+    pub fn scaling_factor(&self, name: &str) -> f64 {
+        self.columns
+            .iter()
+            .position(|c| c == name)
+            .and_then(|i| self.scaling_factors.get(i))
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+/// Borrowed version of [`ViewPreset`] for writing, it has the same fields
+#[derive(serde::Serialize)]
+pub struct PresetRef<'a> {
+    pub columns: &'a [String],
+    pub x_axis: &'a str,
+    pub scaling_factors: &'a [f64],
+    pub derived_columns: &'a [DerivedColumn],
+    pub row_filter: &'a RowFilter,
+    pub filters: &'a FilterPipelines,
+    pub spectrum: &'a SpectrumView,
+    pub trajectory: &'a TrajectoryView,
+    pub styles: &'a ChartStyles,
+    pub layout: &'a PlotLayout,
+    pub fit: &'a CurveFit,
+    pub units: &'a Units,
+}
+
+pub fn preset_path(file: &str) -> String {
+    format!("{}.{}", file, PRESET_EXTENSION)
+}
+
+pub fn read_preset(path: &str) -> Result<ViewPreset, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Could not read \"{}\": {}", path, e))?;
+    ron::from_str(&contents).map_err(|e| format!("Could not parse \"{}\": {}", path, e))
+}
+
+pub fn write_preset(path: &str, preset: &PresetRef) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(preset, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())?;
+    fs::write(path, contents).map_err(|e| format!("Could not write \"{}\": {}", path, e))
+}
+
+/// Whether the columns of the file without the units are the columns of the preset without the derived ones
+fn has_columns(file: &str, preset: &PresetRef) -> bool {
+    let Ok(table) = read_table(file) else {
+        return false;
+    };
+    let columns = preset
+        .columns
+        .iter()
+        .filter(|c| !preset.derived_columns.iter().any(|d| d.name == **c));
+    table.header.iter().map(|h| split_unit(h).0).eq(columns.cloned())
+}
+
+/// Writes the preset next to every data file in the folder of `file` that has the same columns,
+/// existing presets are replaced. Annotation and preset files are skipped.
+/// Returns the number of files the preset was written for and the number of data files with other columns.
+pub fn write_preset_to_folder(file: &str, preset: &PresetRef) -> Result<(usize, usize), String> {
+    let folder = match Path::new(file).parent() {
+        Some(folder) if !folder.as_os_str().is_empty() => folder,
+        _ => Path::new("."),
+    };
+    let entries = fs::read_dir(folder)
+        .map_err(|e| format!("Could not read the folder \"{}\": {}", folder.display(), e))?;
+    let (mut count, mut skipped) = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = path.display().to_string();
+        let sidecar = name.ends_with(&sidecar_path("")) || name.ends_with(PRESET_EXTENSION);
+        if !path.is_file() || sidecar || DataFormat::from_path(&name).is_none() {
+            continue;
+        }
+        if has_columns(&name, preset) {
+            write_preset(&preset_path(&name), preset)?;
+            count += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+    Ok((count, skipped))
+}
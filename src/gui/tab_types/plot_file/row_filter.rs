@@ -0,0 +1,195 @@
+use crate::gui::plot_tools::layout::format_value;
+use crate::gui::tab_types::plotter::compiler::Compiler;
+use crate::gui::tab_types::plotter::vm::VM;
+use egui::{Color32, DragValue, Ui};
+use egui_plot::{PlotPoints, PlotUi, Polygon};
+
+/// What the mask was computed for: expression, crop, x axis and x scaling factor
+type MaskKey = (String, Option<(f64, f64)>, i16, f64);
+
+/// Hides rows of the file without changing it, by an expression over the columns and by an x range
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct RowFilter {
+    /// rows for which this is 0 are hidden, an empty expression keeps every row
+    pub expression: String,
+    /// x range the data is cropped to
+    pub crop: Option<(f64, f64)>,
+    /// dragging over the plot selects the crop instead of moving the plot
+    #[serde(skip)]
+    pub selecting: bool,
+    /// start and end of the range that is currently dragged
+    #[serde(skip)]
+    brush: Option<(f64, f64)>,
+    #[serde(skip)]
+    mask: Vec<bool>,
+    #[serde(skip)]
+    key: Option<MaskKey>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl RowFilter {
+    pub fn is_active(&self) -> bool {
+        !self.expression.trim().is_empty() || self.crop.is_some()
+    }
+
+    /// Whether the row with the index `row` is shown
+    pub fn keep(&self, row: usize) -> bool {
+        self.mask.get(row).copied().unwrap_or(true)
+    }
+
+    pub fn kept(&self) -> usize {
+        self.mask.iter().filter(|k| **k).count()
+    }
+
+    /// Forces the mask to be recomputed, has to be called when the values of the rows change
+    pub fn invalidate(&mut self) {
+        self.key = None;
+    }
+
+    pub fn needs_update(&self, x_axis: i16, x_scale: f64, rows: usize) -> bool {
+        self.mask.len() != rows || self.key.as_ref() != Some(&self.make_key(x_axis, x_scale))
+    }
+
+    fn make_key(&self, x_axis: i16, x_scale: f64) -> MaskKey {
+        (self.expression.clone(), self.crop, x_axis, x_scale)
+    }
+
+    /// Evaluates the expression for every row, the columns are bound to `variables`.
+    /// `x` is the x value of every row in the plot and is used for the crop.
+    pub fn update(
+        &mut self,
+        variables: &[String],
+        rows: &[Vec<Option<f32>>],
+        x: &[Option<f64>],
+        x_axis: i16,
+        x_scale: f64,
+    ) {
+        self.key = Some(self.make_key(x_axis, x_scale));
+        self.error = None;
+        self.mask = vec![true; rows.len()];
+        if let Some((start, end)) = self.crop {
+            for (keep, x) in self.mask.iter_mut().zip(x) {
+                *keep = x.is_some_and(|x| x >= start && x <= end);
+            }
+        }
+        if self.expression.trim().is_empty() {
+            return;
+        }
+        let (instructions, identifiers) =
+            match Compiler::new().optimized_compile(self.expression.clone()) {
+                Ok(compiled) => compiled,
+                Err(e) => {
+                    self.error = Some(e);
+                    return;
+                }
+            };
+        let mut columns = vec![];
+        for identifier in &identifiers {
+            match variables.iter().position(|v| v == identifier) {
+                Some(i) => columns.push(i),
+                None => {
+                    self.error = Some(format!("Unknown variable: \"{}\"", identifier));
+                    return;
+                }
+            }
+        }
+        let mut values = vec![0.0; identifiers.len()];
+        for (keep, row) in self.mask.iter_mut().zip(rows) {
+            if !*keep {
+                continue;
+            }
+            // rows with a missing value of a used column are hidden
+            let complete = columns.iter().zip(values.iter_mut()).all(|(c, v)| {
+                match row.get(*c).copied().flatten() {
+                    Some(value) => {
+                        *v = value as f64;
+                        true
+                    }
+                    None => false,
+                }
+            });
+            *keep = complete
+                && match VM::run((&instructions, &identifiers), &values) {
+                    Ok(result) => result != 0.0 && !result.is_nan(),
+                    Err(e) => {
+                        self.error = Some(e);
+                        false
+                    }
+                };
+        }
+    }
+
+    pub fn allow_plot_drag(&self) -> bool {
+        !self.selecting
+    }
+
+    /// Lets the user drag over the plot to select the x range of the crop
+    pub fn draw(&mut self, plot_ui: &mut PlotUi) {
+        if !self.selecting {
+            return;
+        }
+        let response = plot_ui.response().clone();
+        if let Some(pointer) = plot_ui.pointer_coordinate() {
+            if response.drag_started() {
+                self.brush = Some((pointer.x, pointer.x));
+            } else if response.dragged() {
+                if let Some(brush) = &mut self.brush {
+                    brush.1 = pointer.x;
+                }
+            }
+        }
+        if let Some((start, end)) = self.brush {
+            let bounds = plot_ui.plot_bounds();
+            let (bottom, top) = (bounds.min()[1], bounds.max()[1]);
+            plot_ui.polygon(
+                Polygon::new(PlotPoints::from(vec![
+                    [start, bottom],
+                    [end, bottom],
+                    [end, top],
+                    [start, top],
+                ]))
+                .fill_color(Color32::from_rgba_unmultiplied(100, 150, 255, 40))
+                .stroke(egui::Stroke::new(1.0, Color32::LIGHT_BLUE)),
+            );
+            if response.drag_stopped() {
+                if start != end {
+                    self.crop = Some((start.min(end), start.max(end)));
+                }
+                self.brush = None;
+                self.selecting = false;
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, total: usize) {
+        ui.horizontal(|ui| {
+            ui.label("Show rows where");
+            ui.add(egui::TextEdit::singleline(&mut self.expression).hint_text("speed > 0 && t < 30"));
+        });
+        ui.label("Comparisons: < <= > >= == !=, logic: && || !");
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.selecting, "Select range on plot")
+                .on_hover_text("Drag over the plot to crop the data to an x range");
+            if self.crop.is_some() && ui.button("Reset range").clicked() {
+                self.crop = None;
+            }
+        });
+        if let Some((start, end)) = &mut self.crop {
+            ui.horizontal(|ui| {
+                ui.label("x from");
+                ui.add(DragValue::new(start).speed(0.1));
+                ui.label("to");
+                ui.add(DragValue::new(end).speed(0.1));
+                ui.label(format!("(width {})", format_value(*end - *start)));
+            });
+        }
+        if self.is_active() {
+            ui.label(format!("Showing {} of {} rows", self.kept(), total));
+        }
+    }
+}
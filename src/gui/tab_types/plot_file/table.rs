@@ -0,0 +1,63 @@
+/// The contents of a csv file with a header line, `#` comments and numeric values
+#[derive(Clone, Default)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Option<f32>>>,
+    /// the comments with their line number and the index of the data row that follows them
+    pub comments: Vec<(usize, usize, String)>,
+    /// values that couldn't be parsed are None, this is the error of the last one
+    pub parse_error: Option<String>,
+}
+
+impl Table {
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.header.iter().position(|h| h == name)
+    }
+}
+
+/// Parses a csv file, using the first line that isn't a comment as the header.
+/// Returns None if there is no header.
+pub fn parse_csv(contents: &str) -> Option<Table> {
+    let mut table = Table::default();
+    let mut has_header = false;
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('#') {
+            // this is a comment, comments in front of the header can hold directives like units
+            table.comments.push((
+                line_number,
+                table.rows.len(),
+                line.trim_start_matches('#').trim().to_string(),
+            ));
+            continue;
+        }
+        if !has_header {
+            table.header = line.split(',').map(|s| s.trim().to_string()).collect();
+            has_header = true;
+            continue;
+        }
+        let mut row = Vec::new();
+        for value in line.split(',') {
+            // strip whitespaces
+            let value = value.trim();
+            row.push(match value.parse::<f32>() {
+                Ok(v) => Some(v),
+                Err(_) => match &*value.to_ascii_lowercase() {
+                    "null" | "none" => None,
+                    _ => {
+                        table.parse_error = Some(format!(
+                            "Could not parse value \"{}\" (line {})",
+                            value, line_number
+                        ));
+                        None
+                    }
+                },
+            });
+        }
+        table.rows.push(row);
+    }
+    has_header.then_some(table)
+}
@@ -0,0 +1,263 @@
+use egui::Ui;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Exponents of time, length and angle
+type Dimension = [i8; 3];
+
+const TIME: Dimension = [1, 0, 0];
+const LENGTH: Dimension = [0, 1, 0];
+const ANGLE: Dimension = [0, 0, 1];
+
+/// Units that can be converted, with their dimension and the factor to the SI unit
+const BASE_UNITS: [(&str, Dimension, f64); 18] = [
+    ("s", TIME, 1.0),
+    ("ms", TIME, 1e-3),
+    ("us", TIME, 1e-6),
+    ("µs", TIME, 1e-6),
+    ("ns", TIME, 1e-9),
+    ("min", TIME, 60.0),
+    ("h", TIME, 3600.0),
+    ("m", LENGTH, 1.0),
+    ("cm", LENGTH, 1e-2),
+    ("mm", LENGTH, 1e-3),
+    ("um", LENGTH, 1e-6),
+    ("µm", LENGTH, 1e-6),
+    ("km", LENGTH, 1e3),
+    ("in", LENGTH, 0.0254),
+    ("rad", ANGLE, 1.0),
+    ("deg", ANGLE, PI / 180.0),
+    ("°", ANGLE, PI / 180.0),
+    ("rev", ANGLE, 2.0 * PI),
+];
+
+/// Units that aren't a quotient of base units
+const NAMED_UNITS: [(&str, &str); 3] = [("rpm", "rev/min"), ("Hz", "1/s"), ("kHz", "1/ms")];
+
+/// Units offered for conversion, only the ones with the same dimension as the unit of the file are shown
+const COMMON_UNITS: [&str; 27] = [
+    "s", "ms", "us", "min", "h", "m", "cm", "mm", "km", "rad", "deg", "rev", "m/s", "cm/s", "mm/s",
+    "km/h", "rad/s", "deg/s", "rpm", "Hz", "kHz", "m/s^2", "mm/s^2", "rad/s^2", "deg/s^2", "m/ms",
+    "mm/ms",
+];
+
+/// A unit that is known well enough to be converted
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Unit {
+    dimension: Dimension,
+    /// value of 1 of this unit in SI units
+    factor: f64,
+}
+
+/// Parses one side of a quotient like `s^2`, `s²` or `mm`
+fn parse_power(text: &str) -> Option<Unit> {
+    let text = text.trim();
+    let (name, power) = if let Some((name, power)) = text.split_once('^') {
+        (name, power.trim().parse::<i8>().ok()?)
+    } else if let Some(name) = text.strip_suffix('²') {
+        (name, 2)
+    } else if let Some(name) = text.strip_suffix('³') {
+        (name, 3)
+    } else {
+        (text, 1)
+    };
+    if name.trim() == "1" {
+        return Some(Unit {
+            dimension: [0; 3],
+            factor: 1.0,
+        });
+    }
+    let (_, dimension, factor) = BASE_UNITS.iter().find(|(n, _, _)| *n == name.trim())?;
+    Some(Unit {
+        dimension: dimension.map(|d| d * power),
+        factor: factor.powi(power as i32),
+    })
+}
+
+fn parse_unit(text: &str) -> Option<Unit> {
+    let text = text.trim();
+    let text = NAMED_UNITS
+        .iter()
+        .find(|(name, _)| *name == text)
+        .map(|(_, unit)| *unit)
+        .unwrap_or(text);
+    match text.split_once('/') {
+        Some((numerator, denominator)) => {
+            let (a, b) = (parse_power(numerator)?, parse_power(denominator)?);
+            Some(Unit {
+                dimension: [0, 1, 2].map(|i| a.dimension[i] - b.dimension[i]),
+                factor: a.factor / b.factor,
+            })
+        }
+        None => parse_power(text),
+    }
+}
+
+/// Factor that converts values in `from` to `to`, None if the units are unknown or don't fit together
+pub fn conversion_factor(from: &str, to: &str) -> Option<f64> {
+    if from.trim() == to.trim() {
+        return Some(1.0);
+    }
+    let (from, to) = (parse_unit(from)?, parse_unit(to)?);
+    (from.dimension == to.dimension).then_some(from.factor / to.factor)
+}
+
+/// Units `unit` can be converted to, `unit` itself comes first
+pub fn compatible_units(unit: &str) -> Vec<String> {
+    let mut units = vec![unit.to_string()];
+    for other in COMMON_UNITS {
+        if other != unit && conversion_factor(unit, other).is_some() {
+            units.push(other.to_string());
+        }
+    }
+    units
+}
+
+/// Splits a header like `speed_l [mm/s]` into the name and the unit
+pub fn split_unit(header: &str) -> (String, Option<String>) {
+    let header = header.trim();
+    if let Some(start) = header.strip_suffix(']').and_then(|h| h.rfind('[')) {
+        let unit = header[start + 1..header.len() - 1].trim();
+        let name = header[..start].trim();
+        if !name.is_empty() {
+            return (name.to_string(), (!unit.is_empty()).then(|| unit.to_string()));
+        }
+    }
+    (header.to_string(), None)
+}
+
+/// Splits `a=1, b: 2` into key value pairs
+fn key_values(text: &str) -> Vec<(String, String)> {
+    text.split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').or_else(|| pair.split_once(':'))?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+/// The units of the columns of a file and the units they are shown in.
+/// Units come from headers like `t [ms]` and from `# unit: t=ms, speed=mm/s` comments,
+/// `# meta: key=value` comments are collected as metadata of the file.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Units {
+    /// unit of every column in the file
+    #[serde(skip)]
+    pub file: HashMap<String, String>,
+    #[serde(skip)]
+    pub meta: Vec<(String, String)>,
+    /// unit a column is converted to for showing it
+    pub display: HashMap<String, String>,
+}
+
+impl Units {
+    /// Takes the units out of the header and handles the directives in the comments.
+    /// The directives are removed from `comments`, which are (line, row, text) like the comments of a table.
+    pub fn parse(&mut self, header: &mut [String], comments: &mut Vec<(usize, usize, String)>) {
+        self.file.clear();
+        self.meta.clear();
+        for column in header.iter_mut() {
+            let (name, unit) = split_unit(column);
+            if let Some(unit) = unit {
+                self.file.insert(name.clone(), unit);
+            }
+            *column = name;
+        }
+        comments.retain(|(_, _, text)| {
+            if let Some(units) = text.strip_prefix("unit:") {
+                self.file.extend(key_values(units));
+                false
+            } else if let Some(meta) = text.strip_prefix("meta:") {
+                self.meta.extend(key_values(meta));
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// The unit the column is shown in, None if it has no unit
+    pub fn unit(&self, column: &str) -> Option<&str> {
+        let file = self.file.get(column)?;
+        match self.display.get(column) {
+            Some(display) if conversion_factor(file, display).is_some() => Some(display),
+            _ => Some(file),
+        }
+    }
+
+    /// Factor that converts the values of the column from the unit of the file to the shown unit
+    pub fn factor(&self, column: &str) -> f64 {
+        match (self.file.get(column), self.display.get(column)) {
+            (Some(file), Some(display)) => conversion_factor(file, display).unwrap_or(1.0),
+            _ => 1.0,
+        }
+    }
+
+    /// `name [unit]`, or only the name if the column has no unit
+    pub fn label(&self, column: &str) -> String {
+        match self.unit(column) {
+            Some(unit) => format!("{} [{}]", column, unit),
+            None => column.to_string(),
+        }
+    }
+
+    /// Label for an axis that shows the columns, the units of all columns that have one
+    pub fn axis_label(&self, columns: &[&str]) -> String {
+        let mut units = vec![];
+        for unit in columns.iter().filter_map(|c| self.unit(c)) {
+            if !units.contains(&unit) {
+                units.push(unit);
+            }
+        }
+        match units.len() {
+            0 => String::new(),
+            _ => format!("[{}]", units.join(", ")),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, columns: &[String]) {
+        let columns = columns
+            .iter()
+            .filter_map(|c| Some((c, self.file.get(c)?.clone())))
+            .collect::<Vec<_>>();
+        if columns.is_empty() {
+            ui.label("No units, add them to the header like \"t [ms]\" or as a comment like \"# unit: t=ms\"");
+        }
+        egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            for (column, unit) in columns {
+                ui.label(column);
+                let units = compatible_units(&unit);
+                if units.len() == 1 {
+                    ui.label(&unit);
+                } else {
+                    let mut selected = self.unit(column).unwrap_or(&unit).to_string();
+                    egui::ComboBox::from_id_source(ui.next_auto_id())
+                        .selected_text(&selected)
+                        .show_ui(ui, |ui| {
+                            for unit in &units {
+                                ui.selectable_value(&mut selected, unit.clone(), unit);
+                            }
+                        });
+                    if selected == unit {
+                        self.display.remove(column);
+                    } else {
+                        self.display.insert(column.clone(), selected);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+        if !self.meta.is_empty() {
+            ui.separator();
+            egui::Grid::new(ui.next_auto_id()).striped(true).show(ui, |ui| {
+                for (key, value) in &self.meta {
+                    ui.strong(key);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+        }
+    }
+}
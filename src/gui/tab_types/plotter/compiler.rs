@@ -2,6 +2,7 @@ use crate::gui::tab_types::plotter::functions::{factorial, Function};
 use crate::gui::tab_types::plotter::parser::TokenType::OperationToken;
 use crate::gui::tab_types::plotter::parser::{Operation, Parser, Token};
 use crate::gui::tab_types::plotter::precedence::{get_rule, ParseFn, Precedence};
+use crate::gui::tab_types::plotter::vm::logic_op;
 use std::fmt::Debug;
 
 #[derive(Debug, Clone)]
@@ -201,7 +202,7 @@ impl Compiler {
     }
 
     fn expression(&mut self) {
-        self.parse_precedence(Precedence::Term)
+        self.parse_precedence(Precedence::Or)
     }
 
     fn grouping(&mut self) {
@@ -214,6 +215,8 @@ impl Compiler {
         self.parse_precedence(Precedence::Unary); // evaluate the expression in the unary
         if operator_type == OperationToken(Operation::Subtract) {
             self.push(Operation::Negate)
+        } else if operator_type == OperationToken(Operation::Factorial) {
+            self.push(Operation::Not)
         }
     }
 
@@ -235,6 +238,14 @@ impl Compiler {
                 Operation::Multiply => self.push(Operation::Multiply),
                 Operation::Divide => self.push(Operation::Divide),
                 Operation::Power => self.push(Operation::Power),
+                Operation::Less
+                | Operation::LessEqual
+                | Operation::Greater
+                | Operation::GreaterEqual
+                | Operation::Equal
+                | Operation::NotEqual
+                | Operation::And
+                | Operation::Or => self.push(operator_type),
                 _ => {
                     self.error("Invalid binary operator");
                 }
@@ -311,6 +322,33 @@ impl Compiler {
                     }
                 }
                 Operation::Modulo => binary!(%, Modulo),
+                Operation::Less
+                | Operation::LessEqual
+                | Operation::Greater
+                | Operation::GreaterEqual
+                | Operation::Equal
+                | Operation::NotEqual
+                | Operation::And
+                | Operation::Or => {
+                    let a = stack.pop().unwrap();
+                    let b = stack.pop().unwrap();
+                    if let (Value::Constant(a), Value::Constant(b)) = (a.clone(), b.clone()) {
+                        stack.push(Value::Constant(logic_op(instr, b, a)));
+                    } else {
+                        stack.push(b);
+                        stack.push(a);
+                        stack.push(Value::Operations(vec![instr]));
+                    }
+                }
+                Operation::Not => {
+                    let a = stack.pop().unwrap();
+                    if let Value::Constant(a) = a {
+                        stack.push(Value::Constant(logic_op(Operation::Not, a, 0.0)));
+                    } else {
+                        stack.push(a);
+                        stack.push(Value::Operations(vec![Operation::Not]));
+                    }
+                }
                 Operation::Constant(c) => {
                     stack.push(Value::Constant(c));
                 }
@@ -5,6 +5,8 @@ pub mod precedence;
 pub mod stack;
 pub mod vm;
 
+use crate::export::{Figure, Panel, Series, SeriesShape};
+use crate::gui::plot_tools::cursors::Cursors;
 use crate::gui::tab_types::plot_file::get_color;
 use crate::gui::tab_types::plotter::compiler::Compiler;
 use crate::gui::tab_types::plotter::parser::Operation;
@@ -132,6 +134,7 @@ pub struct Plotter {
     last_parameters: Parameters,
     #[serde(skip)]
     plot_data: Vec<PlotData>,
+    cursors: Cursors,
 }
 
 impl Default for Plotter {
@@ -142,6 +145,7 @@ impl Default for Plotter {
             current_parameters: Parameters::default(),
             last_parameters: Parameters::default(),
             plot_data: Vec::new(),
+            cursors: Cursors::default(),
         }
     }
 }
@@ -213,6 +217,9 @@ impl TabStruct for Plotter {
                 }
             }
         }
+        egui::CollapsingHeader::new("Cursors")
+            .default_open(false)
+            .show(ui, |ui| self.cursors.ui(ui));
     }
 
     fn plot(&mut self, ui: &mut Ui) {
@@ -293,36 +300,68 @@ impl TabStruct for Plotter {
             self.last_input_len = self.inputs.len();
         }
         // plot
+        let allow_drag = self.cursors.allow_plot_drag();
+        self.cursors.clear();
         Plot::new(ui.next_auto_id())
             .legend(Legend::default())
+            .allow_drag(allow_drag)
             .show(ui, |plot_ui| {
-                // convert all plot_data to one long array and look if derivative and integral are non None
-                let data = self.plot_data.iter().fold(Vec::new(), |mut acc, x| {
-                    acc.push((&x.x_y, x.name.clone()));
-                    if let Some(derivative) = &x.derivative {
-                        acc.push((derivative, format!("{}'", x.name)));
-                    }
-                    if let Some(integral) = &x.integral {
-                        acc.push((integral, format!("I{}", x.name)));
-                    }
-                    acc
-                });
-                for (i, data) in data.iter().enumerate() {
+                for (i, (name, points)) in self.lines().into_iter().enumerate() {
+                    self.cursors.measure(&name, &points);
                     plot_ui.line(
-                        Line::new(data.0.iter().map(|x| [x.0, x.1]).collect::<PlotPoints>())
+                        Line::new(PlotPoints::from(points))
                             .color(get_color(i))
-                            .name(data.1.clone()),
+                            .name(name),
                     );
                 }
+                self.cursors.draw(plot_ui);
             });
     }
 
+    fn figure(&mut self) -> Option<Figure> {
+        let series = self
+            .lines()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, points))| Series {
+                name,
+                color: get_color(i),
+                shape: SeriesShape::Line(points),
+            })
+            .collect();
+        Some(Figure {
+            title: "Plotter".to_string(),
+            x_label: "x".to_string(),
+            x_range: Some((self.current_parameters.min, self.current_parameters.max)),
+            panels: vec![Panel {
+                series,
+                ..Default::default()
+            }],
+        })
+    }
+
     fn title(&self) -> String {
         "Plotter".to_string()
     }
 }
 
 impl Plotter {
+    /// All functions with their derivatives and integrals as one list of named lines
+    fn lines(&self) -> Vec<(String, Vec<[f64; 2]>)> {
+        let mut lines = vec![];
+        for data in &self.plot_data {
+            let points = |values: &Vec<(f64, f64)>| values.iter().map(|x| [x.0, x.1]).collect::<Vec<_>>();
+            lines.push((data.name.clone(), points(&data.x_y)));
+            if let Some(derivative) = &data.derivative {
+                lines.push((format!("{}'", data.name), points(derivative)));
+            }
+            if let Some(integral) = &data.integral {
+                lines.push((format!("I{}", data.name), points(integral)));
+            }
+        }
+        lines
+    }
+
     fn gen_data(&mut self) {
         // self.plot_data.clear();
         // compile all the parameters and check for errors
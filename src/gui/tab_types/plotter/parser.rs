@@ -33,6 +33,16 @@ pub enum Operation {
     Call(u8, usize),
     Identifier,
     GetVar(usize),
+    // comparisons and logic, true is 1 and false is 0
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+    Not,
 }
 
 /// Combines Operation and Function
@@ -169,7 +179,48 @@ impl Parser {
             }
             b')' => self.create_token(OperationToken(Operation::CloseParenthesis)),
             b',' => self.create_token(OperationToken(Operation::Comma)),
-            b'!' => self.create_token(OperationToken(Operation::Factorial)),
+            b'!' => {
+                if self.match_char(b'=') {
+                    self.create_token(OperationToken(Operation::NotEqual))
+                } else {
+                    self.create_token(OperationToken(Operation::Factorial))
+                }
+            }
+            b'<' => {
+                if self.match_char(b'=') {
+                    self.create_token(OperationToken(Operation::LessEqual))
+                } else {
+                    self.create_token(OperationToken(Operation::Less))
+                }
+            }
+            b'>' => {
+                if self.match_char(b'=') {
+                    self.create_token(OperationToken(Operation::GreaterEqual))
+                } else {
+                    self.create_token(OperationToken(Operation::Greater))
+                }
+            }
+            b'=' => {
+                if self.match_char(b'=') {
+                    self.create_token(OperationToken(Operation::Equal))
+                } else {
+                    self.error_token("Expected '==' for comparisons")
+                }
+            }
+            b'&' => {
+                if self.match_char(b'&') {
+                    self.create_token(OperationToken(Operation::And))
+                } else {
+                    self.error_token("Expected '&&'")
+                }
+            }
+            b'|' => {
+                if self.match_char(b'|') {
+                    self.create_token(OperationToken(Operation::Or))
+                } else {
+                    self.error_token("Expected '||'")
+                }
+            }
             b'%' => self.create_token(OperationToken(Operation::Modulo)),
             _ => self.error_token("Unexpected character"),
         };
@@ -425,6 +476,16 @@ impl Parser {
         }
     }
 
+    /// Consumes the next char if it is `c`
+    fn match_char(&mut self, c: u8) -> bool {
+        if self.peek() == c {
+            self.cur_pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
     fn advance(&mut self) -> u8 {
         let ret = self.peek();
         self.cur_pos += 1;
@@ -5,6 +5,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Precedence {
     None,
+    Or,
+    And,
+    Equality,
+    Comparison,
     Term,
     Unary,
     Factor,
@@ -37,7 +41,11 @@ pub struct ParseRule {
 impl ParseRule {
     pub fn next_precedence(&self) -> Precedence {
         match self.precedence {
-            Precedence::None => Precedence::Term,
+            Precedence::None => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Term,
             Precedence::Term => Precedence::Unary,
             Precedence::Unary => Precedence::Factor,
             Precedence::Factor => Precedence::Power,
@@ -98,11 +106,36 @@ const PARSE_RULE_NUM: ParseRule = ParseRule {
 };
 
 const PARSE_RULE_FAC: ParseRule = ParseRule {
-    prefix: ParseFn::Grouping,
+    // as a prefix this is the logical not
+    prefix: ParseFn::Unary,
     infix: ParseFn::Factorial,
     precedence: Precedence::Factorial,
 };
 
+const PARSE_RULE_COMPARISON: ParseRule = ParseRule {
+    prefix: ParseFn::None,
+    infix: ParseFn::Binary,
+    precedence: Precedence::Comparison,
+};
+
+const PARSE_RULE_EQUALITY: ParseRule = ParseRule {
+    prefix: ParseFn::None,
+    infix: ParseFn::Binary,
+    precedence: Precedence::Equality,
+};
+
+const PARSE_RULE_AND: ParseRule = ParseRule {
+    prefix: ParseFn::None,
+    infix: ParseFn::Binary,
+    precedence: Precedence::And,
+};
+
+const PARSE_RULE_OR: ParseRule = ParseRule {
+    prefix: ParseFn::None,
+    infix: ParseFn::Binary,
+    precedence: Precedence::Or,
+};
+
 const PARSE_RULE_ID: ParseRule = ParseRule {
     prefix: ParseFn::Variable,
     infix: ParseFn::None,
@@ -121,6 +154,13 @@ pub fn get_rule(token_type: TokenType) -> ParseRule {
             Operation::Factorial => PARSE_RULE_FAC,
             Operation::Power => PARSE_RULE_POWER,
             Operation::Identifier => PARSE_RULE_ID,
+            Operation::Less
+            | Operation::LessEqual
+            | Operation::Greater
+            | Operation::GreaterEqual => PARSE_RULE_COMPARISON,
+            Operation::Equal | Operation::NotEqual => PARSE_RULE_EQUALITY,
+            Operation::And => PARSE_RULE_AND,
+            Operation::Or => PARSE_RULE_OR,
             _ => PARSE_RULE_NONE,
         },
         TokenType::FunctionToken(function) => ParseRule {
@@ -57,6 +57,22 @@ impl VM {
                     push!(factorial(a));
                 }
                 Operation::Modulo => binary_op!( % ),
+                Operation::Less
+                | Operation::LessEqual
+                | Operation::Greater
+                | Operation::GreaterEqual
+                | Operation::Equal
+                | Operation::NotEqual
+                | Operation::And
+                | Operation::Or => {
+                    let a = pop!();
+                    let b = pop!();
+                    push!(logic_op(*instr, b, a));
+                }
+                Operation::Not => {
+                    let a = pop!();
+                    push!(logic_op(Operation::Not, a, 0.0));
+                }
                 Operation::Constant(c) => push!(c),
                 Operation::GetVar(index) => {
                     push!({
@@ -98,3 +114,26 @@ impl VM {
         Ok(stack.pop().unwrap())
     }
 }
+
+/// Evaluates the comparison and logic operators, true is 1 and false is 0.
+/// `a` is the left and `b` the right operand, `Not` only uses `a`.
+pub fn logic_op(operation: Operation, a: f64, b: f64) -> f64 {
+    let truth = |v: f64| v != 0.0;
+    let result = match operation {
+        Operation::Less => a < b,
+        Operation::LessEqual => a <= b,
+        Operation::Greater => a > b,
+        Operation::GreaterEqual => a >= b,
+        Operation::Equal => a == b,
+        Operation::NotEqual => a != b,
+        Operation::And => truth(a) && truth(b),
+        Operation::Or => truth(a) || truth(b),
+        Operation::Not => !truth(a),
+        _ => false,
+    };
+    if result {
+        1.0
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,332 @@
+use crate::gui::tab_types::plot_file::derived::variable_name;
+use crate::gui::tab_types::plotter::compiler::Compiler;
+use crate::gui::tab_types::plotter::parser::Operation;
+use crate::gui::tab_types::plotter::vm::VM;
+use crate::gui::tab_types::tcp_client::decode::decode_value;
+use egui::{Color32, DragValue, Ui};
+use phoenix_rec::Data;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub const ALARM_COLOR: Color32 = Color32::from_rgb(255, 60, 60);
+/// how long the alert is shown after an alarm fired
+const ALERT_DURATION: Duration = Duration::from_secs(5);
+/// the robot sends the time in ms
+const TIME_PER_SECOND: f64 = 1000.0;
+
+/// Splits `sync_error > 5 for 200ms` into the expression and the duration in ms
+fn split_rule(rule: &str) -> Result<(String, f64), String> {
+    let Some((expression, duration)) = rule.rsplit_once(" for ") else {
+        return Ok((rule.trim().to_string(), 0.0));
+    };
+    let duration = duration.trim();
+    let (number, factor) = if let Some(ms) = duration.strip_suffix("ms") {
+        (ms, 1.0)
+    } else if let Some(s) = duration.strip_suffix('s') {
+        (s, TIME_PER_SECOND)
+    } else {
+        return Err(format!("The duration \"{}\" needs a unit, ms or s", duration));
+    };
+    let number = number
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("\"{}\" is not a duration", duration))?;
+    Ok((expression.trim().to_string(), number * factor))
+}
+
+/// A rule in the expression language of the plotter, like `sync_error > 5 for 200ms`.
+/// It fires when the expression is not 0 for at least the duration, and again after it was 0.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct Alarm {
+    pub rule: String,
+    pub enabled: bool,
+    /// save the data around the event to a file when the alarm fires
+    pub capture: bool,
+    /// the compiled expression and the duration in ms, for the rule in `compiled_for`
+    #[serde(skip)]
+    compiled: Option<(Vec<Operation>, Vec<String>, f64)>,
+    #[serde(skip)]
+    compiled_for: String,
+    #[serde(skip)]
+    pub error: Option<String>,
+    /// time at which the expression became true
+    #[serde(skip)]
+    since: Option<f64>,
+    /// the alarm fired and the expression is still true
+    #[serde(skip)]
+    fired: bool,
+    #[serde(skip)]
+    pub count: usize,
+}
+
+impl Default for Alarm {
+    fn default() -> Self {
+        Alarm {
+            rule: String::new(),
+            enabled: true,
+            capture: false,
+            compiled: None,
+            compiled_for: String::new(),
+            error: None,
+            since: None,
+            fired: false,
+            count: 0,
+        }
+    }
+}
+
+impl Alarm {
+    fn compile(&mut self) {
+        if self.compiled_for == self.rule {
+            return;
+        }
+        self.compiled_for = self.rule.clone();
+        self.compiled = None;
+        self.error = None;
+        self.reset();
+        if self.rule.trim().is_empty() {
+            return;
+        }
+        let result = split_rule(&self.rule).and_then(|(expression, duration)| {
+            let (instructions, identifiers) = Compiler::new().optimized_compile(expression)?;
+            Ok((instructions, identifiers, duration))
+        });
+        match result {
+            Ok(compiled) => self.compiled = Some(compiled),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.since = None;
+        self.fired = false;
+        self.count = 0;
+    }
+
+    /// Evaluates the rule at `time` with the current values of the variables, returns true if it fires
+    fn check(&mut self, time: f64, values: &HashMap<String, f64>) -> bool {
+        self.compile();
+        let Some((instructions, identifiers, duration)) = &self.compiled else {
+            return false;
+        };
+        if !self.enabled {
+            return false;
+        }
+        // the rule can't be true before every variable has a value
+        let Some(arguments) = identifiers
+            .iter()
+            .map(|i| values.get(i).copied())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+        let active = match VM::run((instructions, identifiers), &arguments) {
+            Ok(value) => value != 0.0 && !value.is_nan(),
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        };
+        if !active {
+            self.since = None;
+            self.fired = false;
+            return false;
+        }
+        let since = *self.since.get_or_insert(time);
+        if !self.fired && time - since >= *duration {
+            self.fired = true;
+            self.count += 1;
+            return true;
+        }
+        false
+    }
+
+    /// Variables of the rule that aren't channels
+    fn unknown(&self, variables: &[String]) -> Vec<String> {
+        match &self.compiled {
+            Some((_, identifiers, _)) => identifiers
+                .iter()
+                .filter(|i| !variables.contains(i))
+                .cloned()
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+/// An alarm that fired
+#[derive(Clone)]
+pub struct AlarmEvent {
+    /// time of the record at which it fired
+    pub time: f64,
+    pub rule: String,
+    /// the data around it is saved once the records reach this time
+    pub capture_until: Option<f64>,
+}
+
+/// Alarms that are checked for every record that is received or replayed
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Alarms {
+    pub alarms: Vec<Alarm>,
+    /// seconds of data before and after the event that are saved by a capture
+    pub capture_before: f64,
+    pub capture_after: f64,
+    /// folder the captures are saved to
+    pub capture_folder: String,
+    #[serde(skip)]
+    pub events: Vec<AlarmEvent>,
+    /// the latest value of every channel, by variable name
+    #[serde(skip)]
+    values: HashMap<String, f64>,
+    /// when the last alarm fired, for the alert
+    #[serde(skip)]
+    last_alert: Option<Instant>,
+    #[serde(skip)]
+    pub message: String,
+}
+
+impl Default for Alarms {
+    fn default() -> Self {
+        Alarms {
+            alarms: vec![],
+            capture_before: 2.0,
+            capture_after: 2.0,
+            capture_folder: ".".to_string(),
+            events: vec![],
+            values: HashMap::new(),
+            last_alert: None,
+            message: String::new(),
+        }
+    }
+}
+
+impl Alarms {
+    /// Forgets the values and events of the last session
+    pub fn reset(&mut self) {
+        self.events.clear();
+        self.values.clear();
+        self.last_alert = None;
+        self.alarms.iter_mut().for_each(Alarm::reset);
+    }
+
+    /// Updates the values with the record and checks the alarms at its time
+    pub fn push(&mut self, data: &Data) {
+        let (t, values) = match data {
+            Data::RecordData(t, values) => (*t, values.iter().collect::<Vec<_>>()),
+            Data::RecordDataOption(t, values) => (*t, values.iter().flatten().collect()),
+            Data::Command(_) => return,
+        };
+        for value in values {
+            // values that can't be decoded are counted by the decoder
+            for (name, value) in decode_value(value).unwrap_or_default() {
+                self.values.insert(variable_name(&name), value as f64);
+            }
+        }
+        let time = t as f32 as f64;
+        for alarm in &mut self.alarms {
+            if alarm.check(time, &self.values) {
+                self.events.push(AlarmEvent {
+                    time,
+                    rule: alarm.rule.clone(),
+                    capture_until: alarm
+                        .capture
+                        .then_some(time + self.capture_after * TIME_PER_SECOND),
+                });
+                self.last_alert = Some(Instant::now());
+            }
+        }
+    }
+
+    /// The captures whose data is complete at `time`, as (event time, rule, start, end).
+    /// They are only returned once.
+    pub fn due_captures(&mut self, time: f64) -> Vec<(f64, String, f64, f64)> {
+        let before = self.capture_before * TIME_PER_SECOND;
+        let mut due = vec![];
+        for event in &mut self.events {
+            if let Some(until) = event.capture_until {
+                if time >= until {
+                    due.push((event.time, event.rule.clone(), event.time - before, until));
+                    event.capture_until = None;
+                }
+            }
+        }
+        due
+    }
+
+    /// The rules of the alarms that fired in the last seconds
+    pub fn alert(&self) -> Option<String> {
+        let last = self.last_alert?;
+        if last.elapsed() > ALERT_DURATION {
+            return None;
+        }
+        let rules = self
+            .events
+            .iter()
+            .rev()
+            .take(3)
+            .map(|e| e.rule.as_str())
+            .collect::<Vec<_>>();
+        Some(format!("ALARM: {}", rules.join(", ")))
+    }
+
+    /// The alert has to be redrawn until it disappears
+    pub fn alert_pending(&self) -> bool {
+        self.last_alert.is_some_and(|last| last.elapsed() <= ALERT_DURATION)
+    }
+
+    /// `channels` are the names of the channels, they are used as variables with [`variable_name`]
+    pub fn ui(&mut self, ui: &mut Ui, channels: &[String]) {
+        let variables = channels.iter().map(|c| variable_name(c)).collect::<Vec<_>>();
+        ui.label("Rules like \"sync_error > 5 for 200ms\"")
+            .on_hover_text(format!("Variables: {}", variables.join(", ")));
+        let mut to_remove = None;
+        for (i, alarm) in self.alarms.iter_mut().enumerate() {
+            alarm.compile();
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.checkbox(&mut alarm.enabled, "");
+                ui.text_edit_singleline(&mut alarm.rule);
+                ui.checkbox(&mut alarm.capture, "Capture")
+                    .on_hover_text("Save the data around the event to a file");
+                if alarm.count > 0 {
+                    ui.colored_label(ALARM_COLOR, format!("{}x", alarm.count));
+                }
+            });
+            if let Some(error) = &alarm.error {
+                ui.colored_label(Color32::RED, error);
+            } else if !variables.is_empty() {
+                let unknown = alarm.unknown(&variables);
+                if !unknown.is_empty() {
+                    ui.colored_label(Color32::YELLOW, format!("Unknown variables: {}", unknown.join(", ")));
+                }
+            }
+        }
+        if let Some(i) = to_remove {
+            self.alarms.remove(i);
+        }
+        if ui.button("Add alarm").clicked() {
+            self.alarms.push(Alarm::default());
+        }
+        if self.alarms.iter().any(|a| a.capture) {
+            ui.horizontal(|ui| {
+                ui.label("Capture");
+                ui.add(DragValue::new(&mut self.capture_before).speed(0.1).clamp_range(0.0..=600.0).suffix(" s before"));
+                ui.add(DragValue::new(&mut self.capture_after).speed(0.1).clamp_range(0.0..=600.0).suffix(" s after"));
+            });
+            ui.horizontal(|ui| {
+                ui.label("to the folder");
+                ui.text_edit_singleline(&mut self.capture_folder);
+            });
+        }
+        if !self.message.is_empty() {
+            ui.label(&self.message);
+        }
+        for event in self.events.iter().rev().take(10) {
+            ui.label(format!("{:.0}: {}", event.time, event.rule));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::gui::tab_types::tcp_client::connection::command_error;
+use egui::{Color32, DragValue, Ui};
+
+/// Number of sent commands that are kept in the history
+const HISTORY_LEN: usize = 100;
+
+/// A value of the robot that can be changed while it runs, sent as `name=value`
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Parameter {
+    pub name: String,
+    pub value: f64,
+    /// how much the value changes when it is dragged
+    pub step: f64,
+}
+
+impl Default for Parameter {
+    fn default() -> Self {
+        Parameter {
+            name: "".to_string(),
+            value: 0.0,
+            step: 0.01,
+        }
+    }
+}
+
+impl Parameter {
+    fn new(name: &str) -> Self {
+        Parameter {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn command(&self) -> String {
+        format!("{}={}", self.name, self.value)
+    }
+}
+
+/// Parameter updates and free text commands that are sent to the robot, with the sent ones
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CommandPanel {
+    /// the gains of the drive controller by default
+    pub parameters: Vec<Parameter>,
+    #[serde(skip)]
+    text: String,
+    /// the sent commands, the newest last
+    pub history: Vec<String>,
+}
+
+impl Default for CommandPanel {
+    fn default() -> Self {
+        CommandPanel {
+            parameters: vec![
+                Parameter::new("KPDRIVE"),
+                Parameter::new("KIDRIVE"),
+                Parameter::new("KDDRIVE"),
+            ],
+            text: "".to_string(),
+            history: vec![],
+        }
+    }
+}
+
+impl CommandPanel {
+    /// Returns the command the user wants to send. Commands can only be sent if `connected`.
+    pub fn ui(&mut self, ui: &mut Ui, connected: bool) -> Option<String> {
+        let mut command = None;
+        let mut to_remove = None;
+        for (i, parameter) in self.parameters.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.add(egui::TextEdit::singleline(&mut parameter.name).desired_width(80.0));
+                ui.add(DragValue::new(&mut parameter.value).speed(parameter.step));
+                ui.add(DragValue::new(&mut parameter.step).speed(0.001).prefix("step: "));
+                let error = command_error(&parameter.command());
+                let enabled = connected && !parameter.name.is_empty() && error.is_none();
+                if ui.add_enabled(enabled, egui::Button::new("Send")).clicked() {
+                    command = Some(parameter.command());
+                }
+                if let Some(error) = error {
+                    ui.colored_label(Color32::YELLOW, "!").on_hover_text(error);
+                }
+            });
+        }
+        if let Some(i) = to_remove {
+            self.parameters.remove(i);
+        }
+        if ui.button("Add parameter").clicked() {
+            self.parameters.push(Parameter::default());
+        }
+        ui.horizontal(|ui| {
+            let edit = ui.text_edit_singleline(&mut self.text);
+            let enter = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let error = command_error(self.text.trim());
+            let send = ui
+                .add_enabled(connected && error.is_none(), egui::Button::new("Send"))
+                .clicked();
+            if connected && error.is_none() && (enter || send) && !self.text.trim().is_empty() {
+                command = Some(self.text.trim().to_string());
+                self.text.clear();
+            }
+            if let Some(error) = error {
+                ui.colored_label(Color32::YELLOW, error);
+            }
+        });
+        if !self.history.is_empty() {
+            ui.label("History");
+            egui::ScrollArea::vertical()
+                .id_source("command history")
+                .max_height(120.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for sent in &self.history {
+                        // clicking a sent command puts it back into the text field
+                        if ui.selectable_label(false, sent).clicked() {
+                            self.text = sent.clone();
+                        }
+                    }
+                });
+        }
+        command
+    }
+
+    /// Adds a sent command to the history
+    pub fn sent(&mut self, command: String) {
+        self.history.push(command);
+        if self.history.len() > HISTORY_LEN {
+            self.history.remove(0);
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use egui::Color32;
+use lz4_compression::prelude::decompress;
+use phoenix_rec::Data;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The robot reads commands in chunks of this many bytes, longer commands would be split
+pub const MAX_COMMAND_LEN: usize = 48;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// how often the client thread checks if it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// the connection is stalled if no packet arrived for this long
+const STALL_AFTER: Duration = Duration::from_secs(2);
+const FIRST_RETRY: Duration = Duration::from_millis(500);
+const MAX_RETRY: Duration = Duration::from_secs(10);
+/// packets per second are averaged over this long
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    /// connected, but no packet arrived for a while
+    Stalled,
+}
+
+impl ConnectionState {
+    /// Text and color of the state for the interface
+    pub fn label(&self) -> (&'static str, Color32) {
+        match self {
+            ConnectionState::Disconnected => ("Disconnected", Color32::GRAY),
+            ConnectionState::Connecting => ("Connecting", Color32::YELLOW),
+            ConnectionState::Connected => ("Connected", Color32::GREEN),
+            ConnectionState::Stalled => ("Stalled", Color32::from_rgb(255, 140, 0)),
+        }
+    }
+}
+
+/// What the client thread tells the tab
+pub enum ClientEvent {
+    Message(String),
+    Connecting,
+    /// the connection is up, commands are written to this stream
+    Connected(TcpStream),
+    /// a packet with this many bytes and these records arrived
+    Packet(usize, Vec<Data>),
+    /// a packet that couldn't be decoded, it is skipped
+    BadPacket(String),
+    /// the connection was lost, the next attempt is after the duration if the client reconnects
+    Lost(Option<Duration>),
+}
+
+#[derive(Default)]
+pub struct ConnectionStats {
+    pub packets: usize,
+    pub bad_packets: usize,
+    pub bytes: usize,
+    pub records: usize,
+    /// number of times the connection was established again after it was lost
+    pub reconnects: usize,
+    pub last_packet: Option<Instant>,
+    /// arrival times of the packets in the last [`RATE_WINDOW`]
+    recent: VecDeque<Instant>,
+    connected_before: bool,
+}
+
+impl ConnectionStats {
+    fn packet(&mut self, bytes: usize, records: usize) {
+        let now = Instant::now();
+        self.packets += 1;
+        self.bytes += bytes;
+        self.records += records;
+        self.last_packet = Some(now);
+        self.recent.push_back(now);
+        while self.recent.front().is_some_and(|oldest| now - *oldest > RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+    }
+
+    pub fn packets_per_second(&self) -> f64 {
+        let recent = self.recent.iter().filter(|t| t.elapsed() <= RATE_WINDOW).count();
+        recent as f64 / RATE_WINDOW.as_secs_f64()
+    }
+}
+
+/// A connection to the server of the robot, the records are received on a thread and handed to the tab
+/// that owns the connection, so every tab has its own connection. The thread stops when the connection is dropped.
+pub struct Connection {
+    state: ConnectionState,
+    pub stats: ConnectionStats,
+    /// the last message of the client thread
+    pub message: String,
+    /// when the current connection was established
+    connected_at: Option<Instant>,
+    /// when the next attempt to connect is made
+    retry_at: Option<Instant>,
+    stop: Option<Arc<AtomicBool>>,
+    events: Option<Receiver<ClientEvent>>,
+    /// the connection to write commands to
+    writer: Option<TcpStream>,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Connection {
+            state: ConnectionState::Disconnected,
+            stats: ConnectionStats::default(),
+            message: "".to_string(),
+            connected_at: None,
+            retry_at: None,
+            stop: None,
+            events: None,
+            writer: None,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+impl Connection {
+    /// Connects to the server, the connection is established again with a growing delay if `reconnect` is set
+    pub fn connect(&mut self, host: &str, port: u16, reconnect: bool) {
+        self.disconnect();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let address = format!("{}:{}", host, port);
+        let thread_stop = stop.clone();
+        thread::spawn(move || run_client(address, reconnect, thread_stop, sender));
+        self.stats = ConnectionStats::default();
+        self.state = ConnectionState::Connecting;
+        self.message = "Connecting...".to_string();
+        self.stop = Some(stop);
+        self.events = Some(receiver);
+    }
+
+    /// Stops the client thread, the server is told that the connection is closed
+    pub fn disconnect(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop.store(true, Ordering::SeqCst);
+        }
+        if let Some(mut writer) = self.writer.take() {
+            let _ = writer.write_all(b"close");
+            let _ = writer.shutdown(Shutdown::Both);
+        }
+        self.events = None;
+        self.connected_at = None;
+        self.retry_at = None;
+        if self.state != ConnectionState::Disconnected {
+            self.state = ConnectionState::Disconnected;
+            self.message = "Disconnected".to_string();
+        }
+    }
+
+    /// The client thread runs, it might be waiting to reconnect
+    pub fn is_active(&self) -> bool {
+        self.stop.is_some()
+    }
+
+    /// Handles the events of the client thread and returns the records that arrived since the last call,
+    /// has to be called every frame
+    pub fn poll(&mut self) -> Vec<Data> {
+        let mut received = vec![];
+        let Some(events) = &self.events else {
+            return received;
+        };
+        let mut finished = false;
+        loop {
+            match events.try_recv() {
+                Ok(ClientEvent::Message(message)) => self.message = message,
+                Ok(ClientEvent::Connecting) => {
+                    self.state = ConnectionState::Connecting;
+                    self.retry_at = None;
+                }
+                Ok(ClientEvent::Connected(writer)) => {
+                    if self.stats.connected_before {
+                        self.stats.reconnects += 1;
+                    }
+                    self.stats.connected_before = true;
+                    self.writer = Some(writer);
+                    self.state = ConnectionState::Connected;
+                    self.connected_at = Some(Instant::now());
+                }
+                Ok(ClientEvent::Packet(bytes, records)) => {
+                    self.stats.packet(bytes, records.len());
+                    received.extend(records);
+                }
+                Ok(ClientEvent::BadPacket(error)) => {
+                    self.stats.bad_packets += 1;
+                    self.message = error;
+                }
+                Ok(ClientEvent::Lost(retry)) => {
+                    self.writer = None;
+                    self.connected_at = None;
+                    self.state = ConnectionState::Disconnected;
+                    self.retry_at = retry.map(|retry| Instant::now() + retry);
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+        if finished {
+            // the thread gave up
+            self.stop = None;
+            self.events = None;
+        }
+        if matches!(self.state, ConnectionState::Connected | ConnectionState::Stalled) {
+            let since = self.stats.last_packet.max(self.connected_at);
+            let stalled = since.is_some_and(|since| since.elapsed() > STALL_AFTER);
+            self.state = if stalled {
+                ConnectionState::Stalled
+            } else {
+                ConnectionState::Connected
+            };
+        }
+        received
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Time until the next attempt to connect, if the client waits for it
+    pub fn retry_in(&self) -> Option<Duration> {
+        self.retry_at.map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn can_send(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Writes a command to the robot, every command is on its own line
+    pub fn send(&mut self, command: &str) -> Result<(), String> {
+        let Some(writer) = &mut self.writer else {
+            return Err("Not connected".to_string());
+        };
+        send_command(writer, command)
+    }
+}
+
+/// Why the command can't be sent, None if it can
+pub fn command_error(command: &str) -> Option<String> {
+    if command.len() > MAX_COMMAND_LEN {
+        return Some(format!(
+            "The command is {} bytes long, the robot reads at most {}",
+            command.len(),
+            MAX_COMMAND_LEN
+        ));
+    }
+    // the robot ends the session when anything it reads contains "close"
+    if command.contains("close") {
+        return Some("Commands can't contain \"close\", the robot would end the recording".to_string());
+    }
+    None
+}
+
+/// Writes a command to the robot, every command is on its own line
+pub fn send_command(stream: &mut TcpStream, command: &str) -> Result<(), String> {
+    if let Some(error) = command_error(command) {
+        return Err(error);
+    }
+    stream
+        .write_all(format!("{}\n", command).as_bytes())
+        .map_err(|e| format!("Could not send \"{}\": {}", command, e))
+}
+
+/// Connects and does the handshake of phoenix_rec
+fn open(address: &str) -> Result<TcpStream, String> {
+    let addresses = address
+        .to_socket_addrs()
+        .map_err(|e| format!("Invalid address {}: {}", address, e))?;
+    let mut error = format!("{} has no address", address);
+    for socket_address in addresses {
+        match TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT) {
+            Ok(mut stream) => {
+                stream
+                    .set_read_timeout(Some(CONNECT_TIMEOUT))
+                    .map_err(|e| e.to_string())?;
+                let mut reply = [0u8; 5];
+                stream
+                    .write_all(b"hello")
+                    .and_then(|_| stream.read_exact(&mut reply))
+                    .map_err(|e| format!("Handshake with {} failed: {}", address, e))?;
+                if &reply != b"hello" {
+                    return Err(format!(
+                        "Unexpected reply from {}: {}",
+                        address,
+                        String::from_utf8_lossy(&reply)
+                    ));
+                }
+                return Ok(stream);
+            }
+            Err(e) => error = format!("Failed to connect to {}: {}", address, e),
+        }
+    }
+    Err(error)
+}
+
+fn decode_packet(packet: &[u8]) -> Result<Vec<Data>, String> {
+    let data =
+        decompress(packet).map_err(|e| format!("Could not decompress a packet: {:?}", e))?;
+    bincode::deserialize(&data).map_err(|e| format!("Could not decode a packet: {}", e))
+}
+
+/// Receives the packets and sends their records to the tab until the server closes the connection or `stop` is set.
+/// Every packet is the length as u32 followed by the lz4 compressed records.
+fn receive(stream: &mut TcpStream, stop: &AtomicBool, sender: &Sender<ClientEvent>) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|e| e.to_string())?;
+    let mut buffer = vec![];
+    let mut chunk = vec![0u8; 1 << 16];
+    while !stop.load(Ordering::SeqCst) {
+        let n = match stream.read(&mut chunk) {
+            Ok(0) => return Err("The server closed the connection".to_string()),
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(format!("Connection lost: {}", e)),
+        };
+        buffer.extend_from_slice(&chunk[..n]);
+        while buffer.len() >= 4 {
+            let len = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+            if len == 0 {
+                return Err("The server ended the recording".to_string());
+            }
+            if buffer.len() < 4 + len {
+                break;
+            }
+            let event = match decode_packet(&buffer[4..4 + len]) {
+                Ok(records) => ClientEvent::Packet(4 + len, records),
+                Err(e) => ClientEvent::BadPacket(e),
+            };
+            buffer.drain(..4 + len);
+            if sender.send(event).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Connects to the server of phoenix_rec and receives the records it sends until `stop` is set.
+/// If the connection can't be established or is lost, it is tried again with a growing delay if `reconnect` is set.
+fn run_client(address: String, reconnect: bool, stop: Arc<AtomicBool>, sender: Sender<ClientEvent>) {
+    // sending only fails if the tab was closed, then the client stops at the next check of `stop`
+    let send = |event| {
+        if sender.send(event).is_err() {
+            stop.store(true, Ordering::SeqCst);
+        }
+    };
+    let mut retry = FIRST_RETRY;
+    while !stop.load(Ordering::SeqCst) {
+        send(ClientEvent::Connecting);
+        let error = match open(&address) {
+            Ok(mut stream) => {
+                match stream.try_clone() {
+                    Ok(writer) => send(ClientEvent::Connected(writer)),
+                    Err(e) => send(ClientEvent::Message(format!("Can't send commands: {}", e))),
+                }
+                send(ClientEvent::Message(format!("Connected to {}", address)));
+                retry = FIRST_RETRY;
+                match receive(&mut stream, &stop, &sender) {
+                    Ok(()) => break,
+                    Err(e) => e,
+                }
+            }
+            Err(e) => e,
+        };
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        if !reconnect {
+            send(ClientEvent::Message(error));
+            send(ClientEvent::Lost(None));
+            break;
+        }
+        send(ClientEvent::Message(format!("{}, trying again", error)));
+        send(ClientEvent::Lost(Some(retry)));
+        let until = Instant::now() + retry;
+        while Instant::now() < until && !stop.load(Ordering::SeqCst) {
+            thread::sleep(POLL_INTERVAL);
+        }
+        retry = (retry * 2).min(MAX_RETRY);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::signal::decimate::Overview;
+use phoenix_rec::data_types::DataType;
+use phoenix_rec::Data;
+use std::collections::BTreeMap;
+
+/// The values of one channel over time
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Channel {
+    pub name: String,
+    /// (time, value)
+    pub points: Vec<(f32, f32)>,
+    /// the points decimated for drawing all of them, updated with every point
+    pub overview: Overview,
+}
+
+/// Turns the records of the robot into named channels.
+/// Every data type names its values with its description, e.g. `Color(r, l)` becomes the channels
+/// "right color" and "left color", so new data types are decoded without changes here.
+/// Values that can't be decoded are counted in `warnings` instead of stopping the decoding.
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    /// the channels in the order they first appear
+    pub channels: Vec<Channel>,
+    /// the commands with the index of the record they were received at and the time of the record before,
+    /// the time is None for commands in front of the first record
+    pub comments: Vec<(usize, Option<f32>, String)>,
+    /// number of values that couldn't be decoded, by reason
+    pub warnings: BTreeMap<String, usize>,
+    /// number of records that were pushed
+    pub records: usize,
+    /// time of the last record
+    pub time: Option<f32>,
+    /// turns a value into named values, [`decode_value`] unless it is replaced with [`Decoder::with_value_decoder`]
+    value_decoder: ValueDecoder,
+}
+
+/// Turns a value into named values or the reason it can't be decoded
+pub type ValueDecoder = fn(&DataType) -> Result<Vec<(String, f32)>, String>;
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Decoder::with_value_decoder(decode_value)
+    }
+}
+
+impl Decoder {
+    pub fn with_value_decoder(value_decoder: ValueDecoder) -> Decoder {
+        Decoder {
+            channels: vec![],
+            comments: vec![],
+            warnings: BTreeMap::new(),
+            records: 0,
+            time: None,
+            value_decoder,
+        }
+    }
+
+    pub fn decode(data: &[Data]) -> Decoder {
+        let mut decoder = Decoder::default();
+        data.iter().for_each(|d| decoder.push(d));
+        decoder
+    }
+
+    pub fn push(&mut self, data: &Data) {
+        match data {
+            Data::RecordData(t, values) => {
+                self.time = Some(*t as f32);
+                for value in values {
+                    self.push_value(*t, value);
+                }
+            }
+            Data::RecordDataOption(t, values) => {
+                self.time = Some(*t as f32);
+                for value in values.iter().flatten() {
+                    self.push_value(*t, value);
+                }
+            }
+            Data::Command(command) => self.comments.push((self.records, self.time, command.clone())),
+        }
+        self.records += 1;
+    }
+
+    fn push_value(&mut self, t: u128, value: &DataType) {
+        match (self.value_decoder)(value) {
+            Ok(values) => {
+                for (name, value) in values {
+                    let channel = self.channel(&name);
+                    channel.points.push((t as f32, value));
+                    channel.overview.push([t as f32 as f64, value as f64]);
+                }
+            }
+            Err(warning) => *self.warnings.entry(warning).or_insert(0) += 1,
+        }
+    }
+
+    /// The channel with the name, it is added if it doesn't exist yet
+    fn channel(&mut self, name: &str) -> &mut Channel {
+        let index = match self.channels.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => {
+                self.channels.push(Channel {
+                    name: name.to_string(),
+                    points: vec![],
+                    overview: Overview::default(),
+                });
+                self.channels.len() - 1
+            }
+        };
+        &mut self.channels[index]
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.warnings.values().sum()
+    }
+}
+
+/// Name of the variant without the values, e.g. "Color"
+fn variant_name(value: &DataType) -> String {
+    let debug = format!("{:?}", value);
+    debug.split('(').next().unwrap_or(&debug).to_string()
+}
+
+/// The named values of a data type. `None` is a placeholder for missing values and has no values.
+pub fn decode_value(value: &DataType) -> Result<Vec<(String, f32)>, String> {
+    if let DataType::None(_) = value {
+        return Ok(vec![]);
+    }
+    let description = value.write_description();
+    let names = description.split(", ").filter(|n| !n.is_empty()).collect::<Vec<_>>();
+    let text = value.write();
+    let values = text.split(", ").collect::<Vec<_>>();
+    if names.is_empty() {
+        return Err(format!("{} has no channel names", variant_name(value)));
+    }
+    if names.len() != values.len() {
+        return Err(format!(
+            "{} has {} values for {} channels",
+            variant_name(value),
+            values.len(),
+            names.len()
+        ));
+    }
+    names
+        .into_iter()
+        .zip(values)
+        .map(|(name, v)| match v.trim().parse::<f32>() {
+            Ok(v) => Ok((name.to_string(), v)),
+            Err(_) => Err(format!("{} has a value that isn't a number", variant_name(value))),
+        })
+        .collect()
+}
@@ -0,0 +1,693 @@
+pub mod alarms;
+pub mod commands;
+pub mod connection;
+pub mod decode;
+pub mod overlay;
+pub mod recording;
+pub mod view;
+
+use eframe::epaint::Color32;
+use egui_plot::{AxisHints, Legend, Line, Plot, PlotBounds, PlotPoint, PlotPoints, Text, VLine};
+use std::ops::Range;
+use std::path::PathBuf;
+use egui_file::FileDialog;
+
+use crate::export::{Figure, Marker, Panel, Series, SeriesShape};
+use crate::gui::plot_tools::cursors::Cursors;
+use crate::gui::plot_tools::filters::FilterPipelines;
+use crate::gui::plot_tools::layout::{y_range, AxisMap};
+use crate::gui::plot_tools::spectrum::SpectrumView;
+use crate::gui::plot_tools::trajectory::TrajectoryView;
+use crate::gui::tab_types::plot_file::get_color;
+use egui::{Align2, CollapsingHeader, DragValue, Ui};
+use phoenix_rec::client::PORT;
+use phoenix_rec::Data;
+
+use crate::gui::tab_types::tcp_client::alarms::{Alarms, ALARM_COLOR};
+use crate::gui::tab_types::tcp_client::commands::CommandPanel;
+use crate::gui::tab_types::tcp_client::connection::Connection;
+use crate::gui::tab_types::tcp_client::decode::{Channel, Decoder};
+use crate::gui::tab_types::tcp_client::overlay::OverlaySource;
+use crate::gui::tab_types::tcp_client::recording::{write_csv, Replay, Session, SESSION_EXTENSION};
+use crate::gui::tab_types::tcp_client::view::LiveView;
+use crate::gui::tab_types::TabStruct;
+use crate::signal::decimate::{min_max_decimate, visible_range};
+
+/// Lines are reduced to this many buckets of x values, so drawing doesn't get slower as the session grows
+const MAX_BUCKETS: usize = 2048;
+const COMMAND_COLOR: Color32 = Color32::from_rgb(150, 150, 150);
+
+/// A line of the plot: name, points and color, and the points the cursors measure.
+/// Those are all points between the cursors, not decimated and not mapped to the left axis.
+type LiveLine = (String, Vec<[f64; 2]>, Color32, Vec<[f64; 2]>);
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TCPClient {
+    /// everything that was received and sent since connecting, every tab has its own
+    #[serde(skip)]
+    session: Session,
+    /// while a saved session is replayed, its records are added to `session` instead of the received ones
+    #[serde(skip)]
+    replay: Option<Replay>,
+    session_file: String,
+    /// the channels and comments of the records received so far, new records are appended every frame
+    #[serde(skip)]
+    decoder: Decoder,
+    host: String,
+    port: u16,
+    /// connect again with a growing delay when the connection is lost
+    auto_reconnect: bool,
+    #[serde(skip)]
+    connection: Connection,
+    /// messages of the tab, like failed commands
+    #[serde(skip)]
+    message: String,
+    file_name: String,
+    commands: CommandPanel,
+    /// draw the commands as markers on the plot
+    show_commands: bool,
+    /// filter pipelines for the channels, keyed by the channel name
+    filters: FilterPipelines,
+    spectrum: SpectrumView,
+    /// path of the robot from the values of its wheels
+    trajectory: TrajectoryView,
+    cursors: Cursors,
+    /// rolling window and how the channels are drawn
+    view: LiveView,
+    /// rules that are checked for every record
+    alarms: Alarms,
+    /// a second robot or simulator whose channels are drawn over the ones of this connection
+    overlay: OverlaySource,
+    /// x range of the plot in the last frame
+    #[serde(skip)]
+    visible_x: Option<(f64, f64)>,
+    /// x value under the pointer while it is over the plot
+    #[serde(skip)]
+    hovered_x: Option<f64>,
+    /// the plot fitted the x axis to the data in the last frame
+    #[serde(skip)]
+    auto_x: bool,
+}
+
+impl Default for TCPClient {
+    fn default() -> Self {
+        TCPClient {
+            session: Session::default(),
+            replay: None,
+            session_file: format!("./session.{}", SESSION_EXTENSION),
+            decoder: Decoder::default(),
+            host: "localhost".to_string(),
+            port: PORT,
+            auto_reconnect: true,
+            connection: Connection::default(),
+            message: "".to_string(),
+            file_name: "./data.csv".to_string(),
+            commands: CommandPanel::default(),
+            show_commands: true,
+            filters: FilterPipelines::default(),
+            // the time is sent in ms
+            spectrum: SpectrumView::new(1000.0),
+            trajectory: TrajectoryView::default(),
+            cursors: Cursors::default(),
+            view: LiveView::default(),
+            alarms: Alarms::default(),
+            overlay: OverlaySource::default(),
+            visible_x: None,
+            hovered_x: None,
+            auto_x: true,
+        }
+    }
+}
+
+#[typetag::serde]
+impl TabStruct for TCPClient {
+    fn show_interface(&mut self) -> bool {
+        true
+    }
+
+    fn interface(&mut self, ui: &mut Ui) {
+        ui.label("TCP Client");
+        self.connection_ui(ui);
+        if !self.message.is_empty() {
+            ui.label(&self.message);
+        }
+        if !self.decoder.warnings.is_empty() {
+            let count = self.decoder.warning_count();
+            ui.colored_label(Color32::YELLOW, format!("{} values couldn't be decoded", count))
+                .on_hover_text(
+                    self.decoder
+                        .warnings
+                        .iter()
+                        .map(|(reason, count)| format!("{}x {}", count, reason))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+        }
+        CollapsingHeader::new("View")
+            .default_open(true)
+            .show(ui, |ui| {
+                let channels = self
+                    .channel_names()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, name)| (name, get_color(i)))
+                    .collect::<Vec<_>>();
+                self.view.ui(ui, &channels);
+            });
+        CollapsingHeader::new("Overlay source")
+            .default_open(false)
+            .show(ui, |ui| {
+                let start = self.first_time();
+                self.overlay.ui(ui, start);
+            });
+        CollapsingHeader::new("Commands")
+            .default_open(true)
+            .show(ui, |ui| {
+                let connected = self.connection.can_send();
+                if let Some(command) = self.commands.ui(ui, connected) {
+                    self.send(command);
+                }
+            });
+        CollapsingHeader::new("Alarms")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.channel_names();
+                self.alarms.ui(ui, &names);
+            });
+        CollapsingHeader::new("Filters")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.channel_names();
+                self.filters.ui(ui, &names);
+            });
+        CollapsingHeader::new("Spectrum")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.channel_names();
+                self.spectrum.ui(ui, &names);
+            });
+        CollapsingHeader::new("Trajectory")
+            .default_open(false)
+            .show(ui, |ui| {
+                let names = self.channel_names();
+                self.trajectory.ui(ui, &names);
+            });
+        CollapsingHeader::new("Cursors")
+            .default_open(false)
+            .show(ui, |ui| self.cursors.ui(ui));
+        ui.label("Comments");
+        ui.checkbox(&mut self.show_commands, "Show on the plot");
+        for (i, _, comment) in self.decoder.comments.iter() {
+            ui.label(format!("{}: {}", i, comment));
+        }
+        CollapsingHeader::new("Session")
+            .default_open(true)
+            .show(ui, |ui| self.session_ui(ui));
+        ui.label("Save Data");
+        if ui.button("file name").clicked() {
+            if let Some(path) = FileDialog::open_file(Some(PathBuf::from(self.file_name.clone()))).path() {
+                self.file_name = path.display().to_string();
+            }
+        }
+        ui.label(format!("File name: {}", self.file_name));
+        if ui.button("save data").clicked() {
+            let source = format!("Recorded from {}:{}", self.host, self.port);
+            self.message = match write_csv(&self.file_name, self.session.records(), &[source]) {
+                Ok(()) => format!("Saved {} records to \"{}\"", self.session.len(), self.file_name),
+                Err(e) => e,
+            };
+        }
+    }
+
+    fn plot(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("TCP Client");
+            self.view.pause_ui(ui);
+            if let Some(alert) = self.alarms.alert() {
+                ui.colored_label(ALARM_COLOR, alert);
+            }
+        });
+        self.advance_replay(ui);
+        for data in self.connection.poll() {
+            self.session.push(data);
+        }
+        self.overlay.poll();
+        if self.connection.is_active() || self.overlay.is_active() || self.alarms.alert_pending() {
+            // the stats, the stalled state and the alert change without input
+            ui.ctx().request_repaint();
+        }
+        self.read();
+        // the spectrum and the trajectory get the same height as the main plot
+        let panels = 1 + self.spectrum.enabled as usize + self.trajectory.enabled as usize;
+        let height = ui.available_height() / panels as f32;
+        let allow_drag = self.cursors.allow_plot_drag();
+        self.cursors.clear();
+        // the rolling window, otherwise everything while the plot fits the data or the part that was visible
+        let follow = self.view.follow_range(self.last_time());
+        let range = follow.or(if self.auto_x { None } else { self.visible_x });
+        let (lines, map) = self.lines(range);
+        let mut plot = Plot::new("my_plot")
+            .legend(Legend::default())
+            .height(height)
+            .allow_drag(allow_drag);
+        if let Some(map) = map {
+            plot = plot.custom_y_axes(vec![AxisHints::new_y(), map.axis_hints("right axis")]);
+        }
+        plot.show(ui, |plot_ui| {
+            if let Some((min, max)) = follow {
+                let (low, high) = y_range(lines.iter().flat_map(|l| &l.1)).unwrap_or((0.0, 1.0));
+                let margin = ((high - low) * 0.05).max(1e-6);
+                plot_ui.set_plot_bounds(PlotBounds::from_min_max([min, low - margin], [max, high + margin]));
+            } else if self.view.take_resumed() {
+                plot_ui.set_auto_bounds(true.into());
+            } else if self.view.paused && plot_ui.auto_bounds().any() {
+                // keep the view where it is while new records arrive
+                plot_ui.set_plot_bounds(plot_ui.plot_bounds());
+            }
+            for (name, points, color, measured) in lines {
+                self.cursors.measure(&name, &measured);
+                plot_ui.line(Line::new(PlotPoints::from(points)).color(color).name(name));
+            }
+            let top = plot_ui.plot_bounds().max()[1];
+            for marker in self.markers() {
+                plot_ui.vline(VLine::new(marker.x).color(marker.color).width(1.0));
+                plot_ui.text(
+                    Text::new(PlotPoint::new(marker.x, top), format!(" {}", marker.text))
+                        .color(marker.color)
+                        .anchor(Align2::LEFT_TOP),
+                );
+            }
+            self.cursors.draw(plot_ui);
+            let bounds = plot_ui.plot_bounds();
+            self.visible_x = Some((bounds.min()[0], bounds.max()[0]));
+            self.auto_x = plot_ui.auto_bounds().x;
+            self.hovered_x = plot_ui
+                .pointer_coordinate()
+                .filter(|_| plot_ui.response().hovered())
+                .map(|p| p.x);
+        });
+        if self.spectrum.enabled {
+            let points: Vec<[f64; 2]> = self
+                .decoder
+                .channels
+                .iter()
+                .find(|c| c.name == self.spectrum.column)
+                .map(|c| {
+                    let range = visible_range(&c.points, self.visible_x);
+                    c.points[range].iter().map(|(x, y)| [*x as f64, *y as f64]).collect()
+                })
+                .unwrap_or_default();
+            ui.allocate_ui(egui::vec2(ui.available_width(), height), |ui| {
+                self.spectrum.show(ui, &points, self.visible_x);
+            });
+        }
+        if self.trajectory.enabled {
+            let points = |name: &str| -> &[(f32, f32)] {
+                self.decoder
+                    .channels
+                    .iter()
+                    .find(|c| c.name == name)
+                    .map_or(&[], |c| &c.points)
+            };
+            let (right, left) = (points(&self.trajectory.right), points(&self.trajectory.left));
+            // the marker follows the first cursor, the pointer or the newest record while following
+            let cursor = self
+                .cursors
+                .first()
+                .or(self.hovered_x)
+                .or(self.visible_x.map(|(_, max)| max));
+            ui.allocate_ui(egui::vec2(ui.available_width(), height), |ui| {
+                self.trajectory.show(ui, right, left, |(x, y)| [x as f64, y as f64], cursor);
+            });
+        }
+    }
+
+    fn figure(&mut self) -> Option<Figure> {
+        let (lines, map) = self.lines(self.visible_x);
+        let series = lines
+            .into_iter()
+            .map(|(name, points, color, _)| Series {
+                name,
+                color,
+                shape: SeriesShape::Line(points),
+            })
+            .collect();
+        Some(Figure {
+            title: format!("TCP Client: {}:{}", self.host, self.port),
+            x_label: "time".to_string(),
+            x_range: self.visible_x,
+            panels: vec![Panel {
+                series,
+                markers: self.markers(),
+                right_axis: map,
+            }],
+        })
+    }
+
+    fn title(&self) -> String {
+        "TCP Client".to_string()
+    }
+}
+
+/// The x `range` contains the whole channel, None is everything
+fn covers(channel: &Channel, range: Option<(f64, f64)>) -> bool {
+    match (range, channel.points.first(), channel.points.last()) {
+        (Some((min, max)), Some(first), Some(last)) => min <= first.0 as f64 && last.0 as f64 <= max,
+        _ => true,
+    }
+}
+
+/// The points of the channel in the index `range`
+fn raw_points(channel: &Channel, range: Range<usize>) -> Vec<[f64; 2]> {
+    channel.points[range]
+        .iter()
+        .map(|(x, y)| [*x as f64, *y as f64])
+        .collect()
+}
+
+impl TCPClient {
+    fn channel_names(&self) -> Vec<String> {
+        self.decoder.channels.iter().map(|c| c.name.clone()).collect()
+    }
+
+    /// Time of the first record
+    fn first_time(&self) -> Option<f64> {
+        self.decoder
+            .channels
+            .iter()
+            .filter_map(|c| c.points.first())
+            .map(|p| p.0 as f64)
+            .reduce(f64::min)
+    }
+
+    /// Time of the newest record
+    fn last_time(&self) -> Option<f64> {
+        self.decoder
+            .channels
+            .iter()
+            .filter_map(|c| c.points.last())
+            .map(|p| p.0 as f64)
+            .reduce(f64::max)
+    }
+
+    /// The visible channels as they are drawn, with the filtered lines.
+    /// The whole channel is filtered, only the points in the x `range` are drawn and they are decimated to
+    /// [`MAX_BUCKETS`]. When the whole channel is shown the decimated overviews of the channel and of the
+    /// filtered points are drawn, so the cost doesn't grow with the session.
+    /// The channels of the overlay source follow them, moved by its time offset and without filters.
+    /// The lines of the right axis are mapped onto the left one with the returned map.
+    fn lines(&mut self, range: Option<(f64, f64)>) -> (Vec<LiveLine>, Option<AxisMap>) {
+        let cursors = self.cursors.range();
+        let mut left = vec![];
+        let mut right = vec![];
+        for (i, channel) in self.decoder.channels.iter().enumerate() {
+            let view = self.view.channel(&channel.name);
+            if view.hidden {
+                continue;
+            }
+            let color = view.color.unwrap_or(get_color(i));
+            let whole = covers(channel, range);
+            let visible = visible_range(&channel.points, range);
+            let measured = cursors.map(|c| visible_range(&channel.points, Some(c)));
+            let line = (
+                if whole {
+                    channel.overview.points()
+                } else {
+                    raw_points(channel, visible.clone())
+                },
+                measured.clone().map(|m| raw_points(channel, m)).unwrap_or_default(),
+            );
+            let lines = self.filters.lines_with(&channel.name, line, |filters, _| {
+                let filtered = filters.apply_growing(&channel.points);
+                let measured = measured.map(|m| filtered[m].to_vec()).unwrap_or_default();
+                let points = (!whole).then(|| filtered[visible].to_vec());
+                (points.unwrap_or_else(|| filters.growing_overview()), measured)
+            });
+            for (name, (points, measured), highlighted) in lines {
+                let color = if highlighted {
+                    color
+                } else {
+                    color.gamma_multiply(0.4)
+                };
+                let line = (name, min_max_decimate(&points, MAX_BUCKETS), color, measured);
+                if view.right_axis {
+                    right.push(line);
+                } else {
+                    left.push(line);
+                }
+            }
+        }
+        let offset = self.overlay.offset;
+        let shift = |(min, max): (f64, f64)| (min - offset, max - offset);
+        for (i, channel) in self.overlay.decoder.channels.iter().enumerate() {
+            let view = self.view.channel(&channel.name);
+            if view.hidden {
+                continue;
+            }
+            let mut points = if covers(channel, range.map(shift)) {
+                channel.overview.points()
+            } else {
+                raw_points(channel, visible_range(&channel.points, range.map(shift)))
+            };
+            let mut measured = cursors
+                .map(|c| raw_points(channel, visible_range(&channel.points, Some(shift(c)))))
+                .unwrap_or_default();
+            points.iter_mut().chain(&mut measured).for_each(|p| p[0] += offset);
+            let line = (
+                format!("{} ({})", channel.name, self.overlay.source()),
+                min_max_decimate(&points, MAX_BUCKETS),
+                get_color(self.decoder.channels.len() + i),
+                measured,
+            );
+            if view.right_axis {
+                right.push(line);
+            } else {
+                left.push(line);
+            }
+        }
+        if right.is_empty() {
+            return (left, None);
+        }
+        let map = AxisMap::new(
+            y_range(left.iter().flat_map(|l| &l.1)),
+            y_range(right.iter().flat_map(|l| &l.1)),
+        );
+        for (name, points, color, measured) in right {
+            let points = points.iter().map(|p| [p[0], map.to_left(p[1])]).collect();
+            left.push((format!("{} (right)", name), points, color, measured));
+        }
+        (left, Some(map))
+    }
+
+    fn connection_ui(&mut self, ui: &mut Ui) {
+        let active = self.connection.is_active();
+        egui::Grid::new("connection").num_columns(2).show(ui, |ui| {
+            ui.label("Host");
+            ui.add_enabled(!active, egui::TextEdit::singleline(&mut self.host));
+            ui.end_row();
+            ui.label("Port");
+            ui.add_enabled(!active, DragValue::new(&mut self.port));
+            ui.end_row();
+        });
+        ui.checkbox(&mut self.auto_reconnect, "Reconnect automatically");
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!active, egui::Button::new("Connect")).clicked() {
+                self.replay = None;
+                self.clear(Session::new(format!("{}:{}", self.host, self.port)));
+                self.connection.connect(&self.host, self.port, self.auto_reconnect);
+            }
+            if ui.add_enabled(active, egui::Button::new("Disconnect")).clicked() {
+                self.connection.disconnect();
+            }
+        });
+        let (state, color) = self.connection.state().label();
+        ui.horizontal(|ui| {
+            ui.colored_label(color, state);
+            if let Some(retry) = self.connection.retry_in() {
+                ui.label(format!("reconnecting in {:.1} s", retry.as_secs_f32()));
+            }
+        });
+        ui.label(&self.connection.message);
+        let stats = &self.connection.stats;
+        egui::Grid::new("connection stats").num_columns(2).show(ui, |ui| {
+            ui.label("Packets");
+            ui.label(format!("{} ({:.1}/s)", stats.packets, stats.packets_per_second()));
+            ui.end_row();
+            ui.label("Received");
+            ui.label(format!("{:.1} kB, {} records", stats.bytes as f64 / 1000.0, stats.records));
+            ui.end_row();
+            ui.label("Last packet");
+            ui.label(match stats.last_packet {
+                Some(last) => format!("{:.1} s ago", last.elapsed().as_secs_f32()),
+                None => "-".to_string(),
+            });
+            ui.end_row();
+            if stats.bad_packets > 0 {
+                ui.label("Bad packets");
+                ui.colored_label(Color32::YELLOW, stats.bad_packets.to_string());
+                ui.end_row();
+            }
+            if stats.reconnects > 0 {
+                ui.label("Reconnects");
+                ui.label(stats.reconnects.to_string());
+                ui.end_row();
+            }
+        });
+    }
+
+    /// The commands at the time they were received or sent if they are shown, and the alarms that fired
+    fn markers(&self) -> Vec<Marker> {
+        let commands = self
+            .decoder
+            .comments
+            .iter()
+            .filter(|_| self.show_commands)
+            .filter_map(|(_, time, text)| {
+                Some(Marker {
+                    x: (*time)? as f64,
+                    text: text.clone(),
+                    color: COMMAND_COLOR,
+                })
+            });
+        let alarms = self.alarms.events.iter().map(|event| Marker {
+            x: event.time,
+            text: event.rule.clone(),
+            color: ALARM_COLOR,
+        });
+        commands.chain(alarms).collect()
+    }
+
+    /// Sends a command to the robot and logs it in the recording, so it shows up at the time it was sent
+    fn send(&mut self, command: String) {
+        match self.connection.send(&command) {
+            Ok(()) => {
+                self.session.push(Data::Command(format!("sent {}", command)));
+                self.commands.sent(command);
+            }
+            Err(e) => self.message = e,
+        }
+    }
+
+    fn clear(&mut self, session: Session) {
+        self.session = session;
+        self.decoder = Decoder::default();
+        self.alarms.reset();
+        self.filters.clear_caches();
+        self.trajectory.clear();
+    }
+
+    fn session_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.session_file);
+            if ui.button("...").clicked() {
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("session", &[SESSION_EXTENSION])
+                    .add_filter("all files", &["*"])
+                    .pick_file()
+                {
+                    self.session_file = path.display().to_string();
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.session.is_empty(), egui::Button::new("Save session"))
+                .on_hover_text("Saves the records with the time they arrived and the commands")
+                .clicked()
+            {
+                self.message = match self.session.write(&self.session_file) {
+                    Ok(()) => format!("Saved the session to \"{}\"", self.session_file),
+                    Err(e) => e,
+                };
+            }
+            if ui.button("Replay session").clicked() {
+                match Replay::open(&self.session_file) {
+                    Ok(replay) => {
+                        self.connection.disconnect();
+                        self.clear(Session::new(replay.session.source.clone()));
+                        self.replay = Some(replay);
+                        self.message.clear();
+                    }
+                    Err(e) => self.message = e,
+                }
+            }
+        });
+        if let Some(replay) = &mut self.replay {
+            if replay.ui(ui) {
+                self.replay = None;
+            }
+        }
+    }
+
+    /// Adds the records of the replay up to its position to the session, like received records.
+    /// When the position moves back the session is decoded again from the start.
+    fn advance_replay(&mut self, ui: &Ui) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        replay.advance(ui.input(|i| i.unstable_dt) as f64);
+        if replay.playing {
+            ui.ctx().request_repaint();
+        }
+        let count = replay.count();
+        if count < self.session.len() {
+            self.session = Session::new(replay.session.source.clone());
+            self.decoder = Decoder::default();
+            self.alarms.reset();
+        }
+        for i in self.session.len()..count {
+            self.session
+                .push_at(replay.session.arrival(i), replay.session.records()[i].clone());
+        }
+    }
+
+    /// Decodes the records that were added since the last frame, checks the alarms with them
+    /// and saves the captures that are complete
+    fn read(&mut self) {
+        for data in &self.session.records()[self.decoder.records..] {
+            self.decoder.push(data);
+            self.alarms.push(data);
+        }
+        let Some(time) = self.decoder.time else {
+            return;
+        };
+        for (event, rule, start, end) in self.alarms.due_captures(time as f64) {
+            self.alarms.message = self.capture(event, &rule, start, end);
+        }
+    }
+
+    /// Saves the records from `start` to `end` around an alarm at `time` to the capture folder
+    fn capture(&self, time: f64, rule: &str, start: f64, end: f64) -> String {
+        let name = rule
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let path = PathBuf::from(&self.alarms.capture_folder).join(format!("capture_{}_{:.0}.csv", name, time));
+        let path = path.display().to_string();
+        // commands are kept if the record before them is in the capture
+        let mut inside = false;
+        let records = self
+            .session
+            .records()
+            .iter()
+            .filter(|data| {
+                if let Data::RecordData(t, _) | Data::RecordDataOption(t, _) = data {
+                    let t = *t as f32 as f64;
+                    inside = t >= start && t <= end;
+                }
+                inside
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let comments = [
+            format!("Recorded from {}", self.session.source),
+            format!("Capture of \"{}\" at {:.0}", rule, time),
+        ];
+        match write_csv(&path, &records, &comments) {
+            Ok(()) => format!("Saved {} records around \"{}\" to \"{}\"", records.len(), rule, path),
+            Err(e) => e,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::gui::tab_types::tcp_client::connection::Connection;
+use crate::gui::tab_types::tcp_client::decode::Decoder;
+use crate::gui::tab_types::tcp_client::recording::{write_csv, Session};
+use egui::{DragValue, Ui};
+use phoenix_rec::client::PORT;
+
+/// A second robot or simulator in the same tab, its channels are drawn over the ones of the tab
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct OverlaySource {
+    host: String,
+    port: u16,
+    /// added to the times of its records, so they line up with the ones of the tab
+    pub offset: f64,
+    file_name: String,
+    #[serde(skip)]
+    connection: Connection,
+    #[serde(skip)]
+    session: Session,
+    #[serde(skip)]
+    pub decoder: Decoder,
+    #[serde(skip)]
+    message: String,
+}
+
+impl Default for OverlaySource {
+    fn default() -> Self {
+        OverlaySource {
+            host: "localhost".to_string(),
+            port: PORT,
+            offset: 0.0,
+            file_name: "./overlay.csv".to_string(),
+            connection: Connection::default(),
+            session: Session::default(),
+            decoder: Decoder::default(),
+            message: String::new(),
+        }
+    }
+}
+
+impl OverlaySource {
+    /// Adds the records that arrived since the last frame, has to be called every frame
+    pub fn poll(&mut self) {
+        for data in self.connection.poll() {
+            self.decoder.push(&data);
+            self.session.push(data);
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.connection.is_active()
+    }
+
+    /// Host and port the records came from, it is added to the names of the channels
+    pub fn source(&self) -> &str {
+        &self.session.source
+    }
+
+    /// Time of the first record
+    fn first_time(&self) -> Option<f64> {
+        self.decoder
+            .channels
+            .iter()
+            .filter_map(|c| c.points.first())
+            .map(|p| p.0 as f64)
+            .reduce(f64::min)
+    }
+
+    /// `start` is the time of the first record of the tab, the starts of both can be lined up with it
+    pub fn ui(&mut self, ui: &mut Ui, start: Option<f64>) {
+        let active = self.connection.is_active();
+        egui::Grid::new("overlay connection").num_columns(2).show(ui, |ui| {
+            ui.label("Host");
+            ui.add_enabled(!active, egui::TextEdit::singleline(&mut self.host));
+            ui.end_row();
+            ui.label("Port");
+            ui.add_enabled(!active, DragValue::new(&mut self.port));
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!active, egui::Button::new("Connect")).clicked() {
+                self.session = Session::new(format!("{}:{}", self.host, self.port));
+                self.decoder = Decoder::default();
+                self.connection.connect(&self.host, self.port, true);
+            }
+            if ui.add_enabled(active, egui::Button::new("Disconnect")).clicked() {
+                self.connection.disconnect();
+            }
+        });
+        let (state, color) = self.connection.state().label();
+        ui.horizontal(|ui| {
+            ui.colored_label(color, state);
+            ui.label(format!("{} records", self.session.len()));
+        });
+        ui.label(&self.connection.message);
+        ui.horizontal(|ui| {
+            ui.label("Time offset");
+            ui.add(DragValue::new(&mut self.offset).speed(1.0));
+            let first = self.first_time();
+            if ui
+                .add_enabled(start.is_some() && first.is_some(), egui::Button::new("Align starts"))
+                .on_hover_text("Moves the records so the first ones are at the same time")
+                .clicked()
+            {
+                if let (Some(start), Some(first)) = (start, first) {
+                    self.offset = start - first;
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.file_name);
+            if ui
+                .add_enabled(!self.session.is_empty(), egui::Button::new("save data"))
+                .clicked()
+            {
+                let source = format!("Recorded from {}", self.session.source);
+                self.message = match write_csv(&self.file_name, self.session.records(), &[source]) {
+                    Ok(()) => format!("Saved {} records to \"{}\"", self.session.len(), self.file_name),
+                    Err(e) => e,
+                };
+            }
+        });
+        if !self.message.is_empty() {
+            ui.label(&self.message);
+        }
+    }
+}
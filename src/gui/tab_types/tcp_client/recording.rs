@@ -0,0 +1,227 @@
+use egui::{DragValue, Slider, Ui};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use phoenix_rec::data_types::DataType;
+use phoenix_rec::Data;
+use std::fs;
+use std::io::{Read, Write};
+use std::time::Instant;
+
+/// Writes the records as csv like `phoenix_rec::write_data`: a column for every value of the data types
+/// that occur, missing values are null and commands are comments.
+pub fn write_csv(path: &str, records: &[Data], comments: &[String]) -> Result<(), String> {
+    let values = |data: &Data| -> Vec<DataType> {
+        match data {
+            Data::RecordData(_, values) => values.clone(),
+            Data::RecordDataOption(_, values) => values.iter().flatten().copied().collect(),
+            Data::Command(_) => vec![],
+        }
+    };
+    // the data types that occur, in the order of their index
+    let mut used = records
+        .iter()
+        .flat_map(values)
+        .filter(|v| !matches!(v, DataType::None(_)))
+        .map(|v| v.to_u8())
+        .collect::<Vec<_>>();
+    used.sort_unstable();
+    used.dedup();
+    let kinds = used
+        .iter()
+        .filter_map(|i| DataType::from_repr(*i))
+        .collect::<Vec<_>>();
+
+    let mut contents = format!(
+        "time, {}\n",
+        kinds
+            .iter()
+            .map(|kind| kind.write_description())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    contents += "# Phoenix data\n";
+    for comment in comments {
+        contents += &format!("# {}\n", comment);
+    }
+    for data in records {
+        let t = match data {
+            Data::RecordData(t, _) | Data::RecordDataOption(t, _) => t,
+            Data::Command(command) => {
+                contents += &format!("# {}\n", command);
+                continue;
+            }
+        };
+        let values = values(data);
+        let row = kinds
+            .iter()
+            .map(|kind| match values.iter().find(|v| v.to_u8() == kind.to_u8()) {
+                Some(value) => value.write(),
+                None => DataType::None(kind.to_u8()).write(),
+            })
+            .collect::<Vec<_>>();
+        contents += &format!("{}, {}\n", t, row.join(", "));
+    }
+    fs::write(path, contents).map_err(|e| format!("Could not write \"{}\": {}", path, e))
+}
+
+/// Extension of the session files
+pub const SESSION_EXTENSION: &str = "psession";
+/// written in front of the session, so other files aren't decoded
+const SESSION_MAGIC: [u8; 8] = *b"PHXSES01";
+
+/// Everything that was received and sent while connected, with the time it arrived.
+/// It is saved as gzip compressed bincode, so it can be replayed with the same timing.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+pub struct Session {
+    /// host and port the records came from
+    pub source: String,
+    /// seconds since the start of the session at which every record arrived
+    arrivals: Vec<f64>,
+    records: Vec<Data>,
+    #[serde(skip)]
+    start: Option<Instant>,
+}
+
+impl Session {
+    pub fn new(source: String) -> Self {
+        Session {
+            source,
+            start: Some(Instant::now()),
+            ..Default::default()
+        }
+    }
+
+    pub fn records(&self) -> &[Data] {
+        &self.records
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Adds a record that arrived now
+    pub fn push(&mut self, data: Data) {
+        let arrival = self.start.get_or_insert_with(Instant::now).elapsed().as_secs_f64();
+        self.push_at(arrival, data);
+    }
+
+    /// Adds a record that arrived `arrival` seconds after the start
+    pub fn push_at(&mut self, arrival: f64, data: Data) {
+        self.arrivals.push(arrival);
+        self.records.push(data);
+    }
+
+    /// Seconds from the start to the last record
+    pub fn duration(&self) -> f64 {
+        self.arrivals.last().copied().unwrap_or(0.0)
+    }
+
+    /// Number of records that arrived in the first `time` seconds
+    pub fn count_until(&self, time: f64) -> usize {
+        self.arrivals.partition_point(|arrival| *arrival <= time)
+    }
+
+    pub fn arrival(&self, index: usize) -> f64 {
+        self.arrivals[index]
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        let bytes = bincode::serialize(self).map_err(|e| e.to_string())?;
+        let mut encoder = GzEncoder::new(SESSION_MAGIC.to_vec(), Compression::default());
+        encoder.write_all(&bytes).map_err(|e| e.to_string())?;
+        let bytes = encoder.finish().map_err(|e| e.to_string())?;
+        fs::write(path, bytes).map_err(|e| format!("Could not write \"{}\": {}", path, e))
+    }
+
+    pub fn read(path: &str) -> Result<Session, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Could not read \"{}\": {}", path, e))?;
+        let Some(compressed) = bytes.strip_prefix(&SESSION_MAGIC) else {
+            return Err(format!("\"{}\" is not a session or written by a different version", path));
+        };
+        let mut decoded = vec![];
+        GzDecoder::new(compressed)
+            .read_to_end(&mut decoded)
+            .map_err(|e| format!("Could not decompress \"{}\": {}", path, e))?;
+        let session: Session = bincode::deserialize(&decoded)
+            .map_err(|e| format!("\"{}\" is not a valid session: {}", path, e))?;
+        if session.arrivals.len() != session.records.len() {
+            return Err(format!("\"{}\" is not a valid session: the times don't fit the records", path));
+        }
+        Ok(session)
+    }
+}
+
+/// A saved session that is played back as if it was received live
+pub struct Replay {
+    pub file_name: String,
+    pub session: Session,
+    /// the position in the session in seconds
+    pub time: f64,
+    pub playing: bool,
+    pub speed: f64,
+}
+
+impl Replay {
+    pub fn open(file_name: &str) -> Result<Replay, String> {
+        Ok(Replay {
+            file_name: file_name.to_string(),
+            session: Session::read(file_name)?,
+            time: 0.0,
+            playing: true,
+            speed: 1.0,
+        })
+    }
+
+    /// Moves the position forward by `dt` seconds if it is playing, it stops at the end
+    pub fn advance(&mut self, dt: f64) {
+        if !self.playing {
+            return;
+        }
+        self.time = (self.time + dt * self.speed).min(self.session.duration());
+        if self.time >= self.session.duration() {
+            self.playing = false;
+        }
+    }
+
+    /// Number of records that arrived until the position
+    pub fn count(&self) -> usize {
+        self.session.count_until(self.time)
+    }
+
+    /// Returns true if the replay should be closed
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        let mut close = false;
+        let duration = self.session.duration();
+        ui.label(format!("Replay of {} from {}", self.file_name, self.session.source));
+        ui.horizontal(|ui| {
+            let label = if self.playing { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                if !self.playing && self.time >= duration {
+                    // play again from the start
+                    self.time = 0.0;
+                }
+                self.playing = !self.playing;
+            }
+            ui.add(
+                DragValue::new(&mut self.speed)
+                    .speed(0.05)
+                    .clamp_range(0.05..=50.0)
+                    .suffix("x"),
+            );
+            if ui.button("Close replay").clicked() {
+                close = true;
+            }
+        });
+        ui.add(
+            Slider::new(&mut self.time, 0.0..=duration)
+                .text(format!("of {:.1} s", duration))
+                .suffix(" s"),
+        );
+        close
+    }
+}
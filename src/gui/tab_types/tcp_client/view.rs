@@ -0,0 +1,120 @@
+use egui::{Color32, DragValue, Ui};
+use std::collections::HashMap;
+
+/// the robot sends the time in ms
+const TIME_PER_SECOND: f64 = 1000.0;
+
+/// How a channel of the live plot is drawn
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct ChannelView {
+    pub hidden: bool,
+    /// overrides the default color of the channel
+    pub color: Option<Color32>,
+    /// use the y axis on the right side
+    pub right_axis: bool,
+}
+
+/// Which part of the session the live plot shows and how the channels are drawn.
+/// It is saved with the tab, so the layout can be used for the next session.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct LiveView {
+    /// the plot follows the last this many seconds, everything is shown if it is 0
+    pub window: f64,
+    /// the view doesn't move while paused, but the records are still received
+    #[serde(skip)]
+    pub paused: bool,
+    /// the view was paused or the window removed, the plot has to fit the data again
+    #[serde(skip)]
+    resumed: bool,
+    /// keyed by the channel name
+    pub channels: HashMap<String, ChannelView>,
+}
+
+impl Default for LiveView {
+    fn default() -> Self {
+        LiveView {
+            window: 10.0,
+            paused: false,
+            resumed: false,
+            channels: HashMap::new(),
+        }
+    }
+}
+
+impl LiveView {
+    pub fn channel(&self, name: &str) -> ChannelView {
+        self.channels.get(name).copied().unwrap_or_default()
+    }
+
+    /// The x range of the rolling window that ends at `last`, None if the plot doesn't follow the data
+    pub fn follow_range(&self, last: Option<f64>) -> Option<(f64, f64)> {
+        if self.paused || self.window <= 0.0 {
+            return None;
+        }
+        last.map(|last| (last - self.window * TIME_PER_SECOND, last))
+    }
+
+    /// Returns true once after the view was resumed
+    pub fn take_resumed(&mut self) -> bool {
+        std::mem::take(&mut self.resumed)
+    }
+
+    pub fn pause_ui(&mut self, ui: &mut Ui) {
+        if ui
+            .toggle_value(&mut self.paused, "Pause view")
+            .on_hover_text("Stops the plot from following the data, the records are still received")
+            .changed()
+        {
+            self.resumed = !self.paused;
+        }
+    }
+
+    /// `channels` are the names and default colors of the channels
+    pub fn ui(&mut self, ui: &mut Ui, channels: &[(String, Color32)]) {
+        ui.horizontal(|ui| {
+            ui.label("Show the last");
+            let changed = ui
+                .add(
+                    DragValue::new(&mut self.window)
+                        .speed(0.5)
+                        .clamp_range(0.0..=3600.0)
+                        .suffix(" s"),
+                )
+                .on_hover_text("0 shows everything since connecting")
+                .changed();
+            if changed && self.window <= 0.0 {
+                // fit the plot to all data again
+                self.resumed = true;
+            }
+            self.pause_ui(ui);
+        });
+        egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            for (name, default_color) in channels {
+                let mut channel = self.channel(name);
+                let mut visible = !channel.hidden;
+                if ui.checkbox(&mut visible, "").on_hover_text("Show the channel").changed() {
+                    channel.hidden = !visible;
+                }
+                ui.horizontal(|ui| {
+                    let mut color = channel.color.unwrap_or(*default_color);
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        channel.color = Some(color);
+                    }
+                    if channel.color.is_some() && ui.small_button("x").on_hover_text("Reset color").clicked() {
+                        channel.color = None;
+                    }
+                });
+                ui.label(name);
+                ui.checkbox(&mut channel.right_axis, "Right axis");
+                ui.end_row();
+                if channel != ChannelView::default() {
+                    self.channels.insert(name.clone(), channel);
+                } else {
+                    self.channels.remove(name);
+                }
+            }
+        });
+    }
+}
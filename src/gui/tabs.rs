@@ -6,8 +6,8 @@ use egui::{Context, Id, Ui};
 use egui_dock::{DockArea, DockState, NodeIndex, Style, SurfaceIndex, TabIndex};
 use strum::IntoEnumIterator;
 
+use crate::gui::tab_types::all_colors::upgrade;
 use crate::gui::tab_types::{default_plot, PlotType, TabStruct};
-use crate::gui::tab_types::all_colors::AllColorsPlot;
 use crate::gui::tab_types::PlotType::*;
 use crate::gui::tabs::TabAction::*;
 
@@ -38,7 +38,7 @@ impl Default for Tab {
     fn default() -> Self {
         Self {
             name: "New Tab".to_string(),
-            plot: Box::<AllColorsPlot>::default(),
+            plot: default_plot(AllColors),
             plot_type: AllColors,
             id: ID_COUNTER.fetch_add(1, Relaxed),
             node: NodeIndex::root(),
@@ -77,6 +77,15 @@ impl Default for MyTabs {
 }
 
 impl MyTabs {
+    /// Replaces the tabs of old tab types that were loaded from a saved layout
+    pub fn upgrade(&mut self) {
+        for (_, tab) in self.tree.iter_all_tabs_mut() {
+            if let Some(plot) = upgrade(tab.plot.as_ref()) {
+                tab.plot = plot;
+            }
+        }
+    }
+
     pub fn new() -> Self {
         let tree = DockState::new(vec![
             Tab::new(AllColors, 1),
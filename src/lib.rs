@@ -10,8 +10,10 @@ use crate::gui::app::PhoenixGUI;
 
 // exclude this on gui mode
 pub mod data_sets;
+pub mod export;
 pub mod matrix;
 pub mod neural_network;
+pub mod signal;
 // gui stuff
 
 pub mod data;
@@ -0,0 +1,36 @@
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Direction in which a signal has to cross the threshold of a trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+impl Display for Edge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// x value at which `y` first crosses `threshold` in the direction `edge`,
+/// linearly interpolated between the two samples around the crossing
+pub fn first_crossing(x: &[f64], y: &[f64], threshold: f64, edge: Edge) -> Option<f64> {
+    for i in 1..x.len().min(y.len()) {
+        let (a, b) = (y[i - 1], y[i]);
+        let rising = a < threshold && b >= threshold;
+        let falling = a > threshold && b <= threshold;
+        let crossed = match edge {
+            Edge::Rising => rising,
+            Edge::Falling => falling,
+            Edge::Both => rising || falling,
+        };
+        if crossed {
+            let t = (threshold - a) / (b - a);
+            return Some(x[i - 1] + (x[i] - x[i - 1]) * t);
+        }
+    }
+    None
+}
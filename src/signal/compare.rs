@@ -0,0 +1,151 @@
+use crate::signal::fft::fft;
+
+/// Common grids are never finer than this, so two long recordings can't allocate huge vectors
+pub const MAX_GRID_POINTS: usize = 1_000_000;
+
+/// How much a series differs from a reference series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffStats {
+    /// number of grid points where both series have a value
+    pub count: usize,
+    pub rmse: f64,
+    /// largest absolute difference and the x value where it occurs
+    pub max_deviation: f64,
+    pub max_at: f64,
+    /// shift in x with the highest cross-correlation, positive if the series lags behind the reference
+    pub lag: f64,
+    /// normalized cross-correlation at the lag, 1 if the shapes match perfectly
+    pub correlation: f64,
+}
+
+/// Values of the series at the x values of the sorted `grid`, linearly interpolated.
+/// None outside of the points and in gaps that have a missing value on both sides.
+pub fn resample(points: &[[f64; 2]], grid: &[f64]) -> Vec<Option<f64>> {
+    let mut points = points
+        .iter()
+        .filter(|p| p[0].is_finite() && p[1].is_finite())
+        .copied()
+        .collect::<Vec<_>>();
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    let mut i = 0;
+    grid.iter()
+        .map(|x| {
+            // the grid is sorted, so the segment only moves forward
+            while i + 1 < points.len() && points[i + 1][0] < *x {
+                i += 1;
+            }
+            match (points.get(i), points.get(i + 1)) {
+                (Some(a), _) if a[0] == *x => Some(a[1]),
+                (Some(a), Some(b)) if a[0] <= *x && *x <= b[0] => {
+                    if b[0] == a[0] {
+                        Some(a[1])
+                    } else {
+                        Some(a[1] + (b[1] - a[1]) * (x - a[0]) / (b[0] - a[0]))
+                    }
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Evenly spaced x values over the range both series cover.
+/// If `step` isn't positive, the mean spacing of the denser series is used.
+pub fn common_grid(a: &[[f64; 2]], b: &[[f64; 2]], step: f64) -> Vec<f64> {
+    let range = |points: &[[f64; 2]]| {
+        let x = points.iter().map(|p| p[0]).filter(|x| x.is_finite());
+        let min = x.clone().fold(f64::INFINITY, f64::min);
+        let max = x.fold(f64::NEG_INFINITY, f64::max);
+        (min, max)
+    };
+    let ((a_min, a_max), (b_min, b_max)) = (range(a), range(b));
+    let (start, end) = (a_min.max(b_min), a_max.min(b_max));
+    if !start.is_finite() || !end.is_finite() || end < start {
+        return vec![];
+    }
+    let step = if step > 0.0 {
+        step
+    } else {
+        let spacing = |points: &[[f64; 2]], min: f64, max: f64| (max - min) / (points.len().max(2) - 1) as f64;
+        spacing(a, a_min, a_max).min(spacing(b, b_min, b_max))
+    };
+    if step <= 0.0 || !step.is_finite() {
+        return vec![start];
+    }
+    let count = (((end - start) / step).floor() as usize + 1).min(MAX_GRID_POINTS);
+    (0..count).map(|i| start + i as f64 * step).collect()
+}
+
+/// Resamples both series onto the grid and compares `series` to `reference`.
+/// Returns the difference `series - reference` at every grid point where both have a value.
+pub fn diff(reference: &[[f64; 2]], series: &[[f64; 2]], grid: &[f64]) -> (Vec<[f64; 2]>, Option<DiffStats>) {
+    let a = resample(reference, grid);
+    let b = resample(series, grid);
+    let difference = grid
+        .iter()
+        .zip(a.iter().zip(&b))
+        .filter_map(|(x, (a, b))| Some([*x, (*b)? - (*a)?]))
+        .collect::<Vec<_>>();
+    if difference.is_empty() {
+        return (difference, None);
+    }
+    let count = difference.len();
+    let rmse = (difference.iter().map(|d| d[1] * d[1]).sum::<f64>() / count as f64).sqrt();
+    let max = difference
+        .iter()
+        .max_by(|a, b| a[1].abs().total_cmp(&b[1].abs()))
+        .copied()
+        .unwrap_or([0.0, 0.0]);
+    let step = if grid.len() > 1 { grid[1] - grid[0] } else { 0.0 };
+    let (lag, correlation) = cross_correlation_lag(&a, &b).unwrap_or((0, 0.0));
+    let stats = DiffStats {
+        count,
+        rmse,
+        max_deviation: max[1].abs(),
+        max_at: max[0],
+        lag: lag as f64 * step,
+        correlation,
+    };
+    (difference, Some(stats))
+}
+
+/// Shift in samples that maximizes the normalized cross-correlation of `a` and `b`, and the correlation there.
+/// Missing values count as the mean. The lag is positive if `b` lags behind `a`, it is at most half the length.
+pub fn cross_correlation_lag(a: &[Option<f64>], b: &[Option<f64>]) -> Option<(isize, f64)> {
+    let centered = |values: &[Option<f64>]| {
+        let known = values.iter().flatten();
+        let count = known.clone().count();
+        if count == 0 {
+            return None;
+        }
+        let mean = known.sum::<f64>() / count as f64;
+        Some(values.iter().map(|v| v.map(|v| v - mean).unwrap_or(0.0)).collect::<Vec<_>>())
+    };
+    let (a, b) = (centered(a)?, centered(b)?);
+    let energy = (a.iter().map(|v| v * v).sum::<f64>() * b.iter().map(|v| v * v).sum::<f64>()).sqrt();
+    if energy == 0.0 {
+        return None;
+    }
+    // zero padded to twice the length, so the correlation doesn't wrap around
+    let n = (a.len().max(b.len()) * 2).next_power_of_two();
+    let transform = |values: &[f64]| {
+        let mut re = values.to_vec();
+        re.resize(n, 0.0);
+        let mut im = vec![0.0; n];
+        fft(&mut re, &mut im);
+        (re, im)
+    };
+    let (a_re, a_im) = transform(&a);
+    let (b_re, b_im) = transform(&b);
+    // conj(A) * B, conjugated again so the forward fft computes the inverse
+    let mut re = (0..n).map(|i| a_re[i] * b_re[i] + a_im[i] * b_im[i]).collect::<Vec<_>>();
+    let mut im = (0..n).map(|i| -(a_re[i] * b_im[i] - a_im[i] * b_re[i])).collect::<Vec<_>>();
+    fft(&mut re, &mut im);
+    let max_lag = (a.len().min(b.len()) / 2) as isize;
+    (-max_lag..=max_lag)
+        .map(|lag| {
+            let index = lag.rem_euclid(n as isize) as usize;
+            (lag, re[index] / n as f64 / energy)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
@@ -0,0 +1,174 @@
+/// Reduces the points to the first, the smallest and the largest point of every bucket of x values,
+/// so a line through them looks the same as through all points when there are more points than pixels.
+/// The points have to be sorted by x. Returns the points unchanged if there are at most `2 * buckets`.
+pub fn min_max_decimate(points: &[[f64; 2]], buckets: usize) -> Vec<[f64; 2]> {
+    if points.len() <= 2 * buckets.max(1) {
+        return points.to_vec();
+    }
+    let (first, last) = (points[0][0], points[points.len() - 1][0]);
+    let width = (last - first) / buckets as f64;
+    if width <= 0.0 || !width.is_finite() {
+        return points.to_vec();
+    }
+    let mut result = Vec::with_capacity(3 * buckets + 1);
+    let mut start = 0;
+    while start < points.len() {
+        let bucket = ((points[start][0] - first) / width).floor();
+        let end = start
+            + points[start..]
+                .iter()
+                .position(|p| ((p[0] - first) / width).floor() > bucket)
+                .unwrap_or(points.len() - start);
+        let slice = &points[start..end];
+        let min = slice.iter().min_by(|a, b| a[1].total_cmp(&b[1])).copied();
+        let max = slice.iter().max_by(|a, b| a[1].total_cmp(&b[1])).copied();
+        // the extremes are kept in the order they occur, so the line doesn't zigzag backwards
+        let mut extremes = [slice[0]].into_iter().chain(min).chain(max).collect::<Vec<_>>();
+        extremes.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        extremes.dedup();
+        result.extend(extremes);
+        start = end;
+    }
+    if result.last() != points.last() {
+        result.push(points[points.len() - 1]);
+    }
+    result
+}
+
+/// Index range of the sorted points with an x value in `range`, including one point on either side
+/// so lines continue to the edge of the plot
+pub fn visible_range(points: &[(f32, f32)], range: Option<(f64, f64)>) -> std::ops::Range<usize> {
+    let Some((min, max)) = range else {
+        return 0..points.len();
+    };
+    let start = points.partition_point(|p| (p.0 as f64) < min);
+    let end = points.partition_point(|p| (p.0 as f64) <= max);
+    start.saturating_sub(1)..(end + 1).min(points.len())
+}
+
+/// The first, the smallest and the largest point of a bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bucket {
+    index: i64,
+    first: [f64; 2],
+    min: [f64; 2],
+    max: [f64; 2],
+}
+
+impl Bucket {
+    fn new(index: i64, point: [f64; 2]) -> Self {
+        Bucket {
+            index,
+            first: point,
+            min: point,
+            max: point,
+        }
+    }
+
+    fn add(&mut self, point: [f64; 2]) {
+        if point[1] < self.min[1] {
+            self.min = point;
+        }
+        if point[1] > self.max[1] {
+            self.max = point;
+        }
+    }
+}
+
+/// [`min_max_decimate`] of a series that only grows, updated with every new point instead of
+/// going over the whole series, so it stays cheap while a recording gets longer.
+/// When there are more buckets than `buckets` they become twice as wide and neighbours are merged,
+/// which gives the same buckets as decimating all points with the wider buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overview {
+    buckets: usize,
+    /// x of the first point, the buckets start there
+    start: Option<f64>,
+    /// 0 until two points with different x values were added
+    width: f64,
+    data: Vec<Bucket>,
+    last: Option<[f64; 2]>,
+}
+
+impl Default for Overview {
+    fn default() -> Self {
+        // about the width of a screen in pixels
+        Overview::new(2048)
+    }
+}
+
+impl Overview {
+    pub fn new(buckets: usize) -> Self {
+        Overview {
+            buckets: buckets.max(1),
+            start: None,
+            width: 0.0,
+            data: vec![],
+            last: None,
+        }
+    }
+
+    /// Adds a point, the points have to be added in the order of their x values
+    pub fn push(&mut self, point: [f64; 2]) {
+        if !point[0].is_finite() || !point[1].is_finite() {
+            return;
+        }
+        self.last = Some(point);
+        let start = *self.start.get_or_insert(point[0]);
+        if self.width <= 0.0 && point[0] > start {
+            // every bucket starts with the spacing of the first two points and grows as needed
+            self.width = point[0] - start;
+        }
+        let index = if self.width > 0.0 {
+            ((point[0] - start) / self.width).floor() as i64
+        } else {
+            0
+        };
+        match self.data.last_mut() {
+            // points that go back in x stay in the last bucket
+            Some(bucket) if bucket.index >= index => bucket.add(point),
+            _ => self.data.push(Bucket::new(index, point)),
+        }
+        while self.data.len() > self.buckets {
+            self.merge();
+        }
+    }
+
+    /// Doubles the width of the buckets
+    fn merge(&mut self) {
+        self.width *= 2.0;
+        let mut merged: Vec<Bucket> = Vec::with_capacity(self.data.len() / 2 + 1);
+        for bucket in &self.data {
+            let index = bucket.index.div_euclid(2);
+            match merged.last_mut() {
+                Some(last) if last.index == index => {
+                    last.add(bucket.min);
+                    last.add(bucket.max);
+                }
+                _ => merged.push(Bucket { index, ..*bucket }),
+            }
+        }
+        self.data = merged;
+    }
+
+    /// The decimated points, sorted by x
+    pub fn points(&self) -> Vec<[f64; 2]> {
+        let mut result = Vec::with_capacity(3 * self.data.len() + 1);
+        for bucket in &self.data {
+            // the extremes are kept in the order they occur, so the line doesn't zigzag backwards
+            let mut extremes = [bucket.first, bucket.min, bucket.max];
+            extremes.sort_by(|a, b| a[0].total_cmp(&b[0]));
+            for point in extremes {
+                if result.last() != Some(&point) {
+                    result.push(point);
+                }
+            }
+        }
+        if let Some(last) = self.last {
+            if result.last() != Some(&last) {
+                result.push(last);
+            }
+        }
+        result
+    }
+}
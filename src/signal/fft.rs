@@ -0,0 +1,168 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Window functions that are applied to a segment before the fft, to reduce spectral leakage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl Window {
+    pub fn all() -> [Window; 4] {
+        [
+            Window::Rectangular,
+            Window::Hann,
+            Window::Hamming,
+            Window::Blackman,
+        ]
+    }
+
+    pub fn coefficients(&self, n: usize) -> Vec<f64> {
+        if n < 2 {
+            return vec![1.0; n];
+        }
+        let m = (n - 1) as f64;
+        (0..n)
+            .map(|i| {
+                let t = 2.0 * PI * i as f64 / m;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * t.cos(),
+                    Window::Hamming => 0.54 - 0.46 * t.cos(),
+                    Window::Blackman => 0.42 - 0.5 * t.cos() + 0.08 * (2.0 * t).cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// In place radix-2 fft, the length of `re` and `im` has to be a power of two
+pub fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    assert_eq!(n, im.len());
+    assert!(n.is_power_of_two(), "fft length has to be a power of two");
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(len) {
+            let mut cur_re = 1.0;
+            let mut cur_im = 0.0;
+            for k in 0..len / 2 {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Single sided amplitude spectrum in dB of `y`, sampled with `sample_rate` samples per second.
+/// The mean is removed before the fft, so the DC component doesn't hide everything else.
+/// Returns (frequency, magnitude in dB) pairs.
+pub fn spectrum(y: &[f64], sample_rate: f64, window: Window) -> Vec<(f64, f64)> {
+    if y.len() < 2 {
+        return vec![];
+    }
+    let n = y.len().next_power_of_two();
+    let coefficients = window.coefficients(y.len());
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let mut re = vec![0.0; n];
+    let mut im = vec![0.0; n];
+    for (i, (v, w)) in y.iter().zip(&coefficients).enumerate() {
+        re[i] = (v - mean) * w;
+    }
+    fft(&mut re, &mut im);
+    // scale so a sine with amplitude 1 has a peak of 0 dB, no matter the window
+    let gain = coefficients.iter().sum::<f64>() / 2.0;
+    (0..=n / 2)
+        .map(|k| {
+            let magnitude = (re[k] * re[k] + im[k] * im[k]).sqrt() / gain;
+            (k as f64 * sample_rate / n as f64, to_db(magnitude))
+        })
+        .collect()
+}
+
+pub fn to_db(magnitude: f64) -> f64 {
+    20.0 * magnitude.max(1e-12).log10()
+}
+
+/// Short time fourier transform of a series
+pub struct Spectrogram {
+    /// x value at the center of every segment
+    pub times: Vec<f64>,
+    /// highest frequency, the frequency bins are spread evenly from 0 to this
+    pub max_frequency: f64,
+    /// magnitudes in dB, one vec of frequency bins per segment
+    pub values: Vec<Vec<f64>>,
+}
+
+/// Computes the spectrogram with segments of `segment` samples (rounded up to a power of two)
+/// that overlap by `overlap` (0..1) of their length. At most `max_segments` segments are computed,
+/// for longer series the segments are spread out further.
+pub fn spectrogram(
+    x: &[f64],
+    y: &[f64],
+    sample_rate: f64,
+    window: Window,
+    segment: usize,
+    overlap: f64,
+    max_segments: usize,
+) -> Spectrogram {
+    let segment = segment.max(4).next_power_of_two();
+    let mut result = Spectrogram {
+        times: vec![],
+        max_frequency: sample_rate / 2.0,
+        values: vec![],
+    };
+    if y.len() < segment {
+        return result;
+    }
+    let mut hop = ((segment as f64) * (1.0 - overlap.clamp(0.0, 0.95))).max(1.0) as usize;
+    hop = hop.max((y.len() - segment) / max_segments.max(1) + 1);
+    let mut start = 0;
+    while start + segment <= y.len() {
+        result.times.push(x[start + segment / 2]);
+        result.values.push(
+            spectrum(&y[start..start + segment], sample_rate, window)
+                .into_iter()
+                .map(|(_, db)| db)
+                .collect(),
+        );
+        start += hop;
+    }
+    result
+}
@@ -0,0 +1,408 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Which frequencies a butterworth filter lets through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Pass {
+    Low,
+    High,
+    Band,
+}
+
+impl Display for Pass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Pass::Low => write!(f, "Low pass"),
+            Pass::High => write!(f, "High pass"),
+            Pass::Band => write!(f, "Band pass"),
+        }
+    }
+}
+
+/// A single step of a filter pipeline.
+/// All filters keep the number of samples, so the x values of the series stay the same.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Filter {
+    /// centered moving average over `window` samples
+    MovingAverage { window: usize },
+    /// y\[i\] = alpha * x\[i\] + (1 - alpha) * y\[i - 1\]
+    Exponential { alpha: f64 },
+    /// centered moving median over `window` samples
+    Median { window: usize },
+    /// least squares fit of a polynomial of degree `order` over `window` samples
+    SavitzkyGolay { window: usize, order: usize },
+    /// the cutoff frequencies are in cycles per unit of x, `low` is only used for high and band pass
+    /// and `high` only for low and band pass
+    Butterworth {
+        pass: Pass,
+        order: usize,
+        low: f64,
+        high: f64,
+        zero_phase: bool,
+    },
+    /// dy/dx with central differences
+    Derivative,
+    /// cumulative integral with the trapezoidal rule, starting at 0
+    Integral,
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::MovingAverage { window } => write!(f, "Moving average ({})", window),
+            Filter::Exponential { alpha } => write!(f, "Exponential ({})", alpha),
+            Filter::Median { window } => write!(f, "Median ({})", window),
+            Filter::SavitzkyGolay { window, order } => {
+                write!(f, "Savitzky-Golay ({}, {})", window, order)
+            }
+            Filter::Butterworth { pass, order, .. } => write!(f, "Butterworth {} ({})", pass, order),
+            Filter::Derivative => write!(f, "Derivative"),
+            Filter::Integral => write!(f, "Integral"),
+        }
+    }
+}
+
+impl Filter {
+    /// One filter of every kind with sensible default parameters
+    pub fn all() -> Vec<Filter> {
+        vec![
+            Filter::MovingAverage { window: 5 },
+            Filter::Exponential { alpha: 0.2 },
+            Filter::Median { window: 5 },
+            Filter::SavitzkyGolay {
+                window: 11,
+                order: 3,
+            },
+            Filter::Butterworth {
+                pass: Pass::Low,
+                order: 2,
+                low: 0.001,
+                high: 0.01,
+                zero_phase: true,
+            },
+            Filter::Derivative,
+            Filter::Integral,
+        ]
+    }
+
+    /// Number of samples on either side of a sample that its filtered value depends on,
+    /// None if it depends on all samples before it
+    pub fn context(&self) -> Option<usize> {
+        match *self {
+            Filter::MovingAverage { window } | Filter::Median { window } => Some(window.max(1) / 2),
+            // close to the ends the polynomial of the whole first or last window is used
+            Filter::SavitzkyGolay { window, order } => Some(window.max(order + 2)),
+            Filter::Derivative => Some(1),
+            Filter::Exponential { .. } | Filter::Butterworth { .. } | Filter::Integral => None,
+        }
+    }
+
+    /// Applies the filter and returns the new y values
+    pub fn apply(&self, x: &[f64], y: &[f64]) -> Vec<f64> {
+        if y.len() < 2 {
+            return y.to_vec();
+        }
+        match *self {
+            Filter::MovingAverage { window } => moving_average(y, window),
+            Filter::Exponential { alpha } => exponential(y, alpha),
+            Filter::Median { window } => median(y, window),
+            Filter::SavitzkyGolay { window, order } => savitzky_golay(y, window, order),
+            Filter::Butterworth {
+                pass,
+                order,
+                low,
+                high,
+                zero_phase,
+            } => {
+                let fs = sample_rate(x);
+                let sections = match pass {
+                    Pass::Low => butterworth(order, high, fs, false),
+                    Pass::High => butterworth(order, low, fs, true),
+                    Pass::Band => {
+                        let mut sections = butterworth(order, low, fs, true);
+                        sections.extend(butterworth(order, high, fs, false));
+                        sections
+                    }
+                };
+                if zero_phase {
+                    filtfilt(&sections, y)
+                } else {
+                    lfilter(&sections, y)
+                }
+            }
+            Filter::Derivative => derivative(x, y),
+            Filter::Integral => integral(x, y),
+        }
+    }
+}
+
+/// Runs every filter of the pipeline after each other
+pub fn apply_all(filters: &[Filter], x: &[f64], y: &[f64]) -> Vec<f64> {
+    filters
+        .iter()
+        .fold(y.to_vec(), |y, filter| filter.apply(x, &y))
+}
+
+/// Average number of samples per unit of x
+pub fn sample_rate(x: &[f64]) -> f64 {
+    if x.len() < 2 || x[x.len() - 1] == x[0] {
+        return 1.0;
+    }
+    (x.len() - 1) as f64 / (x[x.len() - 1] - x[0]).abs()
+}
+
+fn moving_average(y: &[f64], window: usize) -> Vec<f64> {
+    let half = window.max(1) / 2;
+    // prefix sums, so every average is O(1)
+    let mut sums = Vec::with_capacity(y.len() + 1);
+    sums.push(0.0);
+    for v in y {
+        sums.push(sums[sums.len() - 1] + v);
+    }
+    (0..y.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + half + 1).min(y.len());
+            (sums[end] - sums[start]) / (end - start) as f64
+        })
+        .collect()
+}
+
+fn exponential(y: &[f64], alpha: f64) -> Vec<f64> {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let mut last = y[0];
+    y.iter()
+        .map(|v| {
+            last = alpha * v + (1.0 - alpha) * last;
+            last
+        })
+        .collect()
+}
+
+fn median(y: &[f64], window: usize) -> Vec<f64> {
+    let half = window.max(1) / 2;
+    let mut buffer = Vec::with_capacity(2 * half + 1);
+    (0..y.len())
+        .map(|i| {
+            buffer.clear();
+            buffer.extend_from_slice(&y[i.saturating_sub(half)..(i + half + 1).min(y.len())]);
+            buffer.sort_by(|a, b| a.total_cmp(b));
+            let mid = buffer.len() / 2;
+            if buffer.len() % 2 == 0 {
+                (buffer[mid - 1] + buffer[mid]) / 2.0
+            } else {
+                buffer[mid]
+            }
+        })
+        .collect()
+}
+
+fn savitzky_golay(y: &[f64], window: usize, order: usize) -> Vec<f64> {
+    // the window has to be odd, bigger than the order and not longer than the data
+    let half = (window.max(order + 2).min(y.len()) - 1) / 2;
+    let window = 2 * half + 1;
+    if half == 0 || order >= window {
+        return y.to_vec();
+    }
+    let center = savitzky_golay_weights(half, order, 0.0);
+    (0..y.len())
+        .map(|i| {
+            if i < half {
+                // fit the polynomial to the first window and evaluate it at our position
+                let weights = savitzky_golay_weights(half, order, i as f64 - half as f64);
+                weights.iter().zip(&y[..window]).map(|(w, v)| w * v).sum()
+            } else if i + half >= y.len() {
+                let start = y.len() - window;
+                let weights =
+                    savitzky_golay_weights(half, order, (i - start) as f64 - half as f64);
+                weights.iter().zip(&y[start..]).map(|(w, v)| w * v).sum()
+            } else {
+                center
+                    .iter()
+                    .zip(&y[i - half..=i + half])
+                    .map(|(w, v)| w * v)
+                    .sum()
+            }
+        })
+        .collect()
+}
+
+/// Weights of the samples in a window of `2 * half + 1` samples, such that the weighted sum is the value
+/// of the least squares polynomial at the offset `t` from the center of the window
+fn savitzky_golay_weights(half: usize, order: usize, t: f64) -> Vec<f64> {
+    let n = order + 1;
+    let offsets = (0..=2 * half)
+        .map(|i| i as f64 - half as f64)
+        .collect::<Vec<_>>();
+    // normal equations: (J^T J) c = e(t), with J[i][k] = offset_i^k and e(t)[k] = t^k
+    let mut a = vec![vec![0.0; n]; n];
+    for (row, a_row) in a.iter_mut().enumerate() {
+        for (col, value) in a_row.iter_mut().enumerate() {
+            *value = offsets.iter().map(|o| o.powi((row + col) as i32)).sum();
+        }
+    }
+    let e = (0..n).map(|k| t.powi(k as i32)).collect::<Vec<_>>();
+    let c = match solve(a, e) {
+        Some(c) => c,
+        None => return vec![1.0 / offsets.len() as f64; offsets.len()],
+    };
+    offsets
+        .iter()
+        .map(|o| (0..n).map(|k| c[k] * o.powi(k as i32)).sum())
+        .collect()
+}
+
+/// Solves the linear system a * x = b with gaussian elimination and partial pivoting
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (value, pivot) in a[row].iter_mut().zip(&pivot_row).skip(col) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// A second order section in direct form II transposed, a0 is normalized to 1
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    fn dc_gain(&self) -> f64 {
+        let den = 1.0 + self.a[0] + self.a[1];
+        if den.abs() < 1e-12 {
+            0.0
+        } else {
+            (self.b[0] + self.b[1] + self.b[2]) / den
+        }
+    }
+
+    fn run(&self, y: &[f64]) -> Vec<f64> {
+        // start in the steady state of the first value, so the filter doesn't jump at the start
+        let first = y[0];
+        let gain = self.dc_gain();
+        let mut z2 = self.b[2] * first - self.a[1] * gain * first;
+        let mut z1 = self.b[1] * first - self.a[0] * gain * first + z2;
+        y.iter()
+            .map(|x| {
+                let out = self.b[0] * x + z1;
+                z1 = self.b[1] * x - self.a[0] * out + z2;
+                z2 = self.b[2] * x - self.a[1] * out;
+                out
+            })
+            .collect()
+    }
+}
+
+/// Designs a butterworth low or high pass filter as a cascade of second order sections
+fn butterworth(order: usize, cutoff: f64, fs: f64, high_pass: bool) -> Vec<Biquad> {
+    let order = order.clamp(1, 16);
+    // the cutoff has to be below the nyquist frequency
+    let cutoff = cutoff.abs().clamp(fs * 1e-6, fs * 0.499);
+    let w0 = 2.0 * PI * cutoff / fs;
+    let (sin, cos) = w0.sin_cos();
+    let mut sections = Vec::new();
+    for k in 0..order / 2 {
+        let q = 1.0 / (2.0 * (PI * (2 * k + 1) as f64 / (2 * order) as f64).sin());
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        let b = if high_pass {
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0]
+        } else {
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0]
+        };
+        sections.push(Biquad {
+            b: [b[0] / a0, b[1] / a0, b[2] / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+        });
+    }
+    if order % 2 == 1 {
+        // first order section for the real pole
+        let k = (w0 / 2.0).tan();
+        let b = if high_pass {
+            [1.0 / (1.0 + k), -1.0 / (1.0 + k), 0.0]
+        } else {
+            [k / (1.0 + k), k / (1.0 + k), 0.0]
+        };
+        sections.push(Biquad {
+            b,
+            a: [(k - 1.0) / (k + 1.0), 0.0],
+        });
+    }
+    sections
+}
+
+fn lfilter(sections: &[Biquad], y: &[f64]) -> Vec<f64> {
+    sections
+        .iter()
+        .fold(y.to_vec(), |y, section| section.run(&y))
+}
+
+/// Runs the filter forwards and backwards, which cancels the phase shift
+fn filtfilt(sections: &[Biquad], y: &[f64]) -> Vec<f64> {
+    // extend the signal with a point reflection at both ends to reduce the transients at the edges
+    let pad = (6 * sections.len()).min(y.len() - 1);
+    let first = y[0];
+    let last = y[y.len() - 1];
+    let mut padded = Vec::with_capacity(y.len() + 2 * pad);
+    padded.extend((1..=pad).rev().map(|i| 2.0 * first - y[i]));
+    padded.extend_from_slice(y);
+    padded.extend((1..=pad).map(|i| 2.0 * last - y[y.len() - 1 - i]));
+    let mut filtered = lfilter(sections, &padded);
+    filtered.reverse();
+    let mut filtered = lfilter(sections, &filtered);
+    filtered.reverse();
+    filtered[pad..pad + y.len()].to_vec()
+}
+
+fn derivative(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = y.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = if i == 0 {
+                (0, 1)
+            } else if i == n - 1 {
+                (n - 2, n - 1)
+            } else {
+                (i - 1, i + 1)
+            };
+            let dx = x[b] - x[a];
+            if dx == 0.0 {
+                0.0
+            } else {
+                (y[b] - y[a]) / dx
+            }
+        })
+        .collect()
+}
+
+fn integral(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let mut sum = 0.0;
+    let mut result = Vec::with_capacity(y.len());
+    result.push(0.0);
+    for i in 1..y.len() {
+        sum += (y[i] + y[i - 1]) * (x[i] - x[i - 1]) / 2.0;
+        result.push(sum);
+    }
+    result
+}
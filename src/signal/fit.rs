@@ -0,0 +1,147 @@
+use crate::signal::filter::solve;
+
+/// Result of a least squares fit
+#[derive(Debug, Clone)]
+pub struct FitResult {
+    pub parameters: Vec<f64>,
+    /// standard errors of the parameters, NaN if they can't be determined
+    pub errors: Vec<f64>,
+    pub r_squared: f64,
+    /// sum of the squared residuals
+    pub rss: f64,
+    /// y - model(x) for every point
+    pub residuals: Vec<f64>,
+    pub iterations: usize,
+}
+
+/// Fits the parameters of `model(parameters, x)` to the points with the Levenberg–Marquardt algorithm.
+/// The jacobian is computed with central finite differences.
+pub fn levenberg_marquardt(
+    model: impl Fn(&[f64], f64) -> Result<f64, String>,
+    x: &[f64],
+    y: &[f64],
+    initial: &[f64],
+    max_iterations: usize,
+) -> Result<FitResult, String> {
+    let n = x.len().min(y.len());
+    let p = initial.len();
+    if n < p.max(1) {
+        return Err(format!("Need at least {} points, got {}", p.max(1), n));
+    }
+    let residuals = |parameters: &[f64]| -> Result<Vec<f64>, String> {
+        (0..n).map(|i| Ok(y[i] - model(parameters, x[i])?)).collect()
+    };
+    let sum_of_squares = |r: &[f64]| {
+        let rss = r.iter().map(|r| r * r).sum::<f64>();
+        if rss.is_finite() {
+            rss
+        } else {
+            f64::INFINITY
+        }
+    };
+    let jacobian = |parameters: &[f64]| -> Result<Vec<Vec<f64>>, String> {
+        let mut columns = vec![];
+        for j in 0..p {
+            let h = f64::EPSILON.sqrt() * parameters[j].abs().max(1.0);
+            let mut plus = parameters.to_vec();
+            let mut minus = parameters.to_vec();
+            plus[j] += h;
+            minus[j] -= h;
+            let column = (0..n)
+                .map(|i| Ok((model(&plus, x[i])? - model(&minus, x[i])?) / (2.0 * h)))
+                .collect::<Result<Vec<f64>, String>>()?;
+            columns.push(column);
+        }
+        Ok(columns)
+    };
+    // J^T J and J^T r from the columns of the jacobian
+    let normal_equations = |columns: &[Vec<f64>], r: &[f64]| {
+        let a = (0..p)
+            .map(|j| (0..p).map(|k| dot(&columns[j], &columns[k])).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let g = (0..p).map(|j| dot(&columns[j], r)).collect::<Vec<_>>();
+        (a, g)
+    };
+
+    let mut parameters = initial.to_vec();
+    let mut r = residuals(&parameters)?;
+    let mut rss = sum_of_squares(&r);
+    if !rss.is_finite() {
+        return Err("The model isn't finite for the initial parameters".to_string());
+    }
+    let mut lambda = 1e-3;
+    let mut iterations = 0;
+    while iterations < max_iterations && p > 0 {
+        iterations += 1;
+        let columns = jacobian(&parameters)?;
+        let (a, g) = normal_equations(&columns, &r);
+        let mut improved = false;
+        while lambda < 1e16 {
+            let mut damped = a.clone();
+            for (j, row) in damped.iter_mut().enumerate() {
+                row[j] += lambda * a[j][j].max(1e-12);
+            }
+            let Some(delta) = solve(damped, g.clone()) else {
+                lambda *= 10.0;
+                continue;
+            };
+            let candidate = parameters
+                .iter()
+                .zip(&delta)
+                .map(|(p, d)| p + d)
+                .collect::<Vec<_>>();
+            let candidate_r = residuals(&candidate)?;
+            let candidate_rss = sum_of_squares(&candidate_r);
+            if candidate_rss < rss {
+                let converged = (rss - candidate_rss) <= 1e-12 * rss.max(1e-300)
+                    || delta
+                        .iter()
+                        .zip(&candidate)
+                        .all(|(d, p)| d.abs() <= 1e-10 * p.abs().max(1e-10));
+                parameters = candidate;
+                r = candidate_r;
+                rss = candidate_rss;
+                lambda = (lambda / 10.0).max(1e-12);
+                improved = !converged;
+                break;
+            }
+            lambda *= 10.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    // the covariance is (J^T J)^-1 * rss / (n - p)
+    let columns = jacobian(&parameters)?;
+    let (a, _) = normal_equations(&columns, &r);
+    let variance = if n > p {
+        rss / (n - p) as f64
+    } else {
+        f64::NAN
+    };
+    let errors = (0..p)
+        .map(|j| {
+            let mut unit = vec![0.0; p];
+            unit[j] = 1.0;
+            match solve(a.clone(), unit) {
+                Some(column) => (column[j] * variance).sqrt(),
+                None => f64::NAN,
+            }
+        })
+        .collect();
+    let mean = y[..n].iter().sum::<f64>() / n as f64;
+    let tss = y[..n].iter().map(|y| (y - mean).powi(2)).sum::<f64>();
+    Ok(FitResult {
+        parameters,
+        errors,
+        r_squared: if tss > 0.0 { 1.0 - rss / tss } else { f64::NAN },
+        rss,
+        residuals: r,
+        iterations,
+    })
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
@@ -0,0 +1,9 @@
+//! Signal processing for recorded time series
+pub mod align;
+pub mod compare;
+pub mod decimate;
+pub mod fft;
+pub mod filter;
+pub mod fit;
+pub mod odometry;
+pub mod stats;
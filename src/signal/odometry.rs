@@ -0,0 +1,139 @@
+//! Differential drive odometry: the path of the robot from the distances or speeds of its wheels
+
+/// Position and heading of the robot at a time
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pose {
+    pub t: f64,
+    pub x: f64,
+    pub y: f64,
+    /// in radians, counter clockwise from the x axis
+    pub heading: f64,
+}
+
+/// Pairs the samples of the right and the left wheel as (t, right, left) at the times of the right one.
+/// The left values are interpolated linearly, times before the first left sample are dropped.
+/// Both have to be sorted by time. The samples can grow, only the right samples that were added since
+/// the last call are paired.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pairing {
+    /// number of right samples that were paired or dropped
+    right: usize,
+    /// index of the left sample at or before the next right one
+    left: usize,
+}
+
+impl Pairing {
+    /// Pairs the new right samples, `point` gives the time and the value of a sample.
+    /// Right samples after the last left one are paired in a later call, when the left samples reached them.
+    pub fn pair<P: Copy>(&mut self, right: &[P], left: &[P], point: impl Fn(P) -> [f64; 2]) -> Vec<(f64, f64, f64)> {
+        let mut result = vec![];
+        while let Some([t, r]) = right.get(self.right).map(|p| point(*p)) {
+            if left.last().is_none_or(|l| point(*l)[0] < t) {
+                break;
+            }
+            while self.left + 1 < left.len() && point(left[self.left + 1])[0] < t {
+                self.left += 1;
+            }
+            self.right += 1;
+            let (a, b) = (point(left[self.left]), left.get(self.left + 1).map(|p| point(*p)));
+            let l = match b {
+                _ if a[0] == t => a[1],
+                Some(b) if a[0] <= t && t <= b[0] => {
+                    if b[0] == a[0] {
+                        b[1]
+                    } else {
+                        a[1] + (b[1] - a[1]) * (t - a[0]) / (b[0] - a[0])
+                    }
+                }
+                _ => continue,
+            };
+            result.push((t, r, l));
+        }
+        result
+    }
+}
+
+/// The step (t, right, left) the wheels drove between two samples of their driven distances since the start,
+/// e.g. `DrivenDistance(r, l)`. The distances are multiplied by `scale`.
+pub fn distance_step(a: (f64, f64, f64), b: (f64, f64, f64), scale: f64) -> (f64, f64, f64) {
+    let ((_, r0, l0), (t, r1, l1)) = (a, b);
+    (t, (r1 - r0) * scale, (l1 - l0) * scale)
+}
+
+/// The step (t, right, left) the wheels drove between two samples of their speeds, e.g. `RealSpeeds(r, l)`,
+/// with the trapezoidal rule. The speeds are multiplied by `scale` and are per second,
+/// the time has `x_per_second` units per second.
+pub fn speed_step(a: (f64, f64, f64), b: (f64, f64, f64), scale: f64, x_per_second: f64) -> (f64, f64, f64) {
+    let ((t0, r0, l0), (t1, r1, l1)) = (a, b);
+    let dt = (t1 - t0) / x_per_second;
+    (t1, (r0 + r1) / 2.0 * dt * scale, (l0 + l1) / 2.0 * dt * scale)
+}
+
+/// The poses of paired samples that are added over time, starting at the origin along the x axis.
+/// New samples are integrated from the last pose, so the earlier ones aren't integrated again.
+#[derive(Debug, Clone, Default)]
+pub struct Odometry {
+    /// the last sample, the next step starts there
+    last: Option<(f64, f64, f64)>,
+    pub poses: Vec<Pose>,
+}
+
+impl Odometry {
+    /// Integrates the samples (t, right, left), `step` turns two samples into the step the wheels drove.
+    /// `wheel_base` is the distance between the wheels, in the unit of the steps.
+    pub fn extend(
+        &mut self,
+        samples: &[(f64, f64, f64)],
+        step: impl Fn((f64, f64, f64), (f64, f64, f64)) -> (f64, f64, f64),
+        wheel_base: f64,
+    ) {
+        for sample in samples.iter().copied() {
+            let Some(last) = self.last.replace(sample) else {
+                self.poses.push(Pose {
+                    t: sample.0,
+                    ..Default::default()
+                });
+                continue;
+            };
+            let (t, right, left) = step(last, sample);
+            if !right.is_finite() || !left.is_finite() {
+                continue;
+            }
+            let pose = *self.poses.last().unwrap();
+            let distance = (right + left) / 2.0;
+            let turn = if wheel_base > 0.0 {
+                (right - left) / wheel_base
+            } else {
+                0.0
+            };
+            // moving along the mean heading of the step is exact for arcs of constant curvature in the limit
+            let heading = pose.heading + turn / 2.0;
+            self.poses.push(Pose {
+                t,
+                x: pose.x + distance * heading.cos(),
+                y: pose.y + distance * heading.sin(),
+                heading: pose.heading + turn,
+            });
+        }
+    }
+}
+
+/// The pose at time `t`, interpolated between the poses around it.
+/// Times before the first or after the last pose get that pose, None if there are no poses.
+pub fn pose_at(poses: &[Pose], t: f64) -> Option<Pose> {
+    let i = poses.partition_point(|p| p.t < t);
+    let Some(b) = poses.get(i) else {
+        return poses.last().copied();
+    };
+    if b.t == t || i == 0 {
+        return Some(*b);
+    }
+    let a = poses[i - 1];
+    let f = (t - a.t) / (b.t - a.t);
+    Some(Pose {
+        t,
+        x: a.x + (b.x - a.x) * f,
+        y: a.y + (b.y - a.y) * f,
+        heading: a.heading + (b.heading - a.heading) * f,
+    })
+}
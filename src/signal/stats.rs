@@ -0,0 +1,49 @@
+/// Value of the series at `x`, linearly interpolated between the two points around it.
+/// The points don't have to be sorted, the first segment that contains `x` is used.
+pub fn interpolate(points: &[[f64; 2]], x: f64) -> Option<f64> {
+    if let [point] = points {
+        return (point[0] == x).then_some(point[1]);
+    }
+    points.windows(2).find_map(|w| {
+        let (a, b) = (w[0], w[1]);
+        let (low, high) = if a[0] <= b[0] { (a, b) } else { (b, a) };
+        if x < low[0] || x > high[0] {
+            return None;
+        }
+        if high[0] == low[0] {
+            return Some(low[1]);
+        }
+        Some(low[1] + (high[1] - low[1]) * (x - low[0]) / (high[0] - low[0]))
+    })
+}
+
+/// Statistics of the points in an x range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionStats {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub rms: f64,
+}
+
+/// Statistics of the y values of all points with `start <= x <= end`, None if there are none
+pub fn region_stats(points: &[[f64; 2]], start: f64, end: f64) -> Option<RegionStats> {
+    let (start, end) = if start <= end { (start, end) } else { (end, start) };
+    let values = points
+        .iter()
+        .filter(|p| p[0] >= start && p[0] <= end && p[1].is_finite())
+        .map(|p| p[1])
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        return None;
+    }
+    let count = values.len();
+    Some(RegionStats {
+        count,
+        mean: values.iter().sum::<f64>() / count as f64,
+        min: values.iter().copied().fold(f64::INFINITY, f64::min),
+        max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        rms: (values.iter().map(|v| v * v).sum::<f64>() / count as f64).sqrt(),
+    })
+}